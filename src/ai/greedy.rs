@@ -0,0 +1,408 @@
+use crate::automaton::*;
+use crate::game_objects::*;
+use crate::game_logic::BoardState;
+use crate::observation::Observation;
+use super::{Strategy, Explanation, candidate_actions};
+
+/// Does whatever helps the most right now: plays cards that help us, then spends what we have in the stores,
+/// then uses buildings with what is left. When nothing is worth doing, the turn ends.
+#[derive(Debug)]
+pub struct Greedy;
+
+impl Strategy for Greedy {
+    fn explain(&self, board: &Observation) -> Explanation {
+        decide(board)
+    }
+}
+
+impl Preferences for Greedy {}
+
+/// What a strategy deciding greedily values differently. The defaults are those of Greedy itself.
+pub trait Preferences {
+    /// How much playing the card in hand would help us; None if it can't be played now
+    fn play_value(&self, board: &BoardState, idx: usize) -> Option<i16> {
+        play_value(board, idx)
+    }
+
+    /// How much buying the card would help us; None if we won't buy it
+    fn buy_value(&self, board: &BoardState, card: &Card) -> Option<i16> {
+        Some(buy_value(board, card))
+    }
+
+    /// Cards with a priority are bought before any other, the lowest priority first
+    fn buy_priority(&self, _card: &Card) -> Option<usize> {
+        None
+    }
+}
+
+/// The greedy decision on any board, e.g. a simulated one
+pub fn decide(board: &BoardState) -> Explanation {
+    decide_with(board, &Greedy)
+}
+
+/// The greedy decision, valued as the preferences say
+pub fn decide_with(board: &BoardState, preferences: &dyn Preferences) -> Explanation {
+    let plays: Vec<(GameEvent, i16)> = (0..board.hand.cards.len())
+        .filter_map(|idx| preferences.play_value(board, idx).map(|value| (GameEvent::CardPicked(idx), value)))
+        .collect();
+
+    let buys: Vec<(GameEvent, &Card, i16)> = candidate_actions(board).into_iter()
+        .filter_map(|action| match action {
+            GameEvent::CardBought(zone, idx) => {
+                let card = &board.stores.iter().find(|s| s.menu.zone == zone)?.menu.cards[idx];
+                preferences.buy_value(board, card).map(|value| (action, card, value))
+            },
+            _ => None
+        })
+        .collect();
+
+    let activations: Vec<(GameEvent, i16)> = board.buildings.cards.iter()
+        .enumerate()
+        .filter(|(_, b)| b.can_activate(&board.globals))
+        .map(|(idx, b)| (GameEvent::CardActivated(idx), effects_value(board, b.effects_on(Trigger::Activate))))
+        .collect();
+
+    let candidates = plays.iter().cloned()
+        .chain(buys.iter().map(|(action, _, value)| (action.clone(), *value)))
+        .chain(activations.iter().cloned())
+        .chain(std::iter::once((GameEvent::EndTurn, 0)))
+        .map(|(action, value)| (action, value as f32))
+        .collect();
+
+    let best_play = plays.iter()
+        .filter(|(_, value)| *value >= 0)
+        .max_by_key(|(_, value)| *value);
+    if let Some((action, value)) = best_play {
+        return Explanation::new(action.clone(), format!("the best card to play, worth {}", value), candidates);
+    }
+
+    let favorite = buys.iter()
+        .filter_map(|buy| preferences.buy_priority(buy.1).map(|priority| (buy, priority)))
+        .min_by_key(|(_, priority)| *priority)
+        .map(|(buy, _)| buy);
+    if let Some((action, card, _)) = favorite {
+        return Explanation::new(action.clone(), format!("{} is on my list", card.name), candidates);
+    }
+
+    let best_buy = buys.iter()
+        .filter(|(_, _, value)| *value > 0)
+        .max_by_key(|(_, card, value)| (*value, -card.effective_cost().count));
+    if let Some((action, card, value)) = best_buy {
+        return Explanation::new(action.clone(), format!("the best card to buy, worth {} for {}", value, card.effective_cost().count), candidates);
+    }
+
+    let activation = activations.iter().find(|(_, value)| *value > 0);
+    if let Some((action, value)) = activation {
+        return Explanation::new(action.clone(), format!("worth using, {}", value), candidates);
+    }
+
+    Explanation::new(GameEvent::EndTurn, "nothing else is worth doing", candidates)
+}
+
+/// Value of a building standing between us and defeat
+pub const BUILDING_VALUE: i16 = 3;
+
+/// How much trouble the kaiju makes; every kaiju makes some
+pub fn kaiju_danger(kaiju: &Card) -> i16 {
+    2 * kaiju.threat() + 1
+}
+
+/// How much playing the card from hand would help us; None if it can't be played now
+pub fn play_value(board: &BoardState, idx: usize) -> Option<i16> {
+    let card = &board.hand.cards[idx];
+    if !card.available {
+        return None;
+    }
+
+    let mut value = effects_value(board, &card.on_play) + target_value(board, idx)?;
+
+    value += match card.extra_cost {
+        Some(ExtraCost::Sacrifice) => -BUILDING_VALUE,
+        Some(ExtraCost::Discard) => -1,
+        Some(ExtraCost::ReturnKaiju) => 1, // gets rid of a kaiju for a while
+        None => 0
+    };
+
+    Some(value)
+}
+
+/// How much the card in hand does to its best target; None if it needs a target and there is none
+pub fn target_value(board: &BoardState, idx: usize) -> Option<i16> {
+    let card = &board.hand.cards[idx];
+    if card.target_zone == BoardZone::None {
+        return Some(0);
+    }
+
+    let target = best_target(board, idx, card.target_zone)?;
+    if card.target_zone != BoardZone::Kaiju {
+        return Some(0);
+    }
+
+    let danger = kaiju_danger(&board.kaiju_zone.cards[target]);
+    Some(match card.target_effect {
+        TargetEffect::Kill => 2 * danger,
+        TargetEffect::Bounce | TargetEffect::Stun => danger,
+        TargetEffect::None => 0,
+    })
+}
+
+/// How much having the card would help us. Kaiju for the opponent are worth as much as they are dangerous.
+pub fn buy_value(board: &BoardState, card: &Card) -> i16 {
+    if card.give_to_enemy {
+        return 2 * kaiju_danger(card);
+    }
+
+    let mut value = effects_value(board, &card.on_play) + 2 * card.points;
+    value += 2 * effects_value(board, &card.on_turn_start); // buildings do it every turn
+    if card.target_zone == BoardZone::Kaiju {
+        value += 2;
+    }
+    if card.activated.is_some() || card.reaction.is_some() {
+        value += 1;
+    }
+    value += (card.auras.len() + card.responses.len()) as i16;
+
+    value
+}
+
+pub fn effects_value(board: &BoardState, effects: &[Effect]) -> i16 {
+    effects.iter().map(|e| effect_value(board, e)).sum()
+}
+
+/// Rough value of an effect for the player it happens to
+fn effect_value(board: &BoardState, effect: &Effect) -> i16 {
+    match effect {
+        Effect::Global{key: Globals::Block, val} => {
+            // block only helps against strikes coming at the end of this turn
+            let incoming: i16 = board.kaiju_zone.cards.iter()
+                .filter(|k| !k.stunned)
+                .map(|k| k.threat())
+                .sum();
+            if incoming > board.globals.get(Globals::Block) { 2 * val } else { 0 }
+        },
+        Effect::Global{val, ..} => *val,
+        Effect::ToBuildings => BUILDING_VALUE + (5 - board.buildings.cards.len() as i16).max(0),
+        Effect::Points{val} => 2 * val,
+        Effect::Break => -1,
+        Effect::BreakUnblockable => -2,
+        Effect::BreakEverything => -5,
+        Effect::Scry{n} | Effect::Peek{n, ..} => (*n as i16 + 1) / 2,
+        Effect::Choose{options, picks} => {
+            let mut values: Vec<i16> = options.iter()
+                .map(|mode| effects_value(board, &mode.effects))
+                .collect();
+            values.sort_by_key(|v| -v);
+            values.iter().take(*picks as usize).sum()
+        },
+        _ => 0
+    }
+}
+
+/// The card to target: the most dangerous kaiju (that is not stunned already, if we stun) the extra cost
+/// can still be paid with
+pub fn best_target(board: &BoardState, card_idx: usize, zone: BoardZone) -> Option<usize> {
+    let stunning = board.hand.cards[card_idx].target_effect == TargetEffect::Stun;
+
+    match zone {
+        BoardZone::Kaiju => board.kaiju_zone.cards.iter()
+            .enumerate()
+            .filter(|(_, k)| !(stunning && k.stunned))
+            .filter(|(idx, _)| board.can_pay_extra(card_idx, Some((zone, *idx))))
+            .max_by_key(|(_, k)| kaiju_danger(k))
+            .map(|(idx, _)| idx),
+        _ => None // nothing targets other zones yet
+    }
+}
+
+pub fn target_card(board: &BoardState, card_idx: usize, card_target: BoardZone) -> GameEvent {
+    match best_target(board, card_idx, card_target) {
+        Some(idx) => GameEvent::CardTargeted(BoardZone::Hand, card_idx, card_target, idx),
+        None => GameEvent::CardTargeted(BoardZone::None, 0, BoardZone::None, 0) // nothing to target
+    }
+}
+
+/// The reaction that helps the most, if any helps more than playing the card would
+pub fn react(board: &BoardState, item: &StackItem, candidates: &[usize]) -> Option<usize> {
+    candidates.iter()
+        .map(|idx| (*idx, reaction_value(board, item, &board.hand.cards[*idx])))
+        .filter(|(_, value)| *value > 0)
+        .max_by_key(|(_, value)| *value)
+        .map(|(idx, _)| idx)
+}
+
+/// What reacting with the card adds to playing it normally. Countering is worth as much as the countered card cost.
+fn reaction_value(board: &BoardState, item: &StackItem, card: &Card) -> i16 {
+    let reaction = match &card.reaction {
+        Some(reaction) => reaction,
+        None => return 0
+    };
+
+    let mut value = effects_value(board, &reaction.effects) - effects_value(board, &card.on_play);
+    if reaction.counter {
+        value += item.source.cost.count.max(1);
+    }
+    value
+}
+
+/// Intercept the card when blocking it is worth more than keeping the intercept. Cards that help the kaiju's
+/// owner, like those meant for the enemy or those hurting the player who plays them, are let through.
+pub fn intercept(board: &BoardState, kaiju: &Card, item: &StackItem) -> bool {
+    let card = &item.source;
+    if card.give_to_enemy {
+        return false;
+    }
+
+    let mut blocked = effects_value(board, &card.on_play);
+    if card.target_zone == BoardZone::Kaiju && card.target_effect != TargetEffect::None {
+        blocked += kaiju_danger(kaiju); // it may be aimed at this very kaiju
+    }
+
+    // the last intercept of the turn is kept for something better than a trifle
+    let keeping = if kaiju.intercepts_left > 1 { 0 } else { 1 };
+    blocked > keeping
+}
+
+/// Kaiju are bad for us and good for the opponent
+pub fn arrange(_board: &BoardState, cards: &[Card], own_deck: bool) -> (Vec<usize>, Vec<usize>) {
+    let (kaiju, others): (Vec<usize>, Vec<usize>) = (0..cards.len())
+        .partition(|i| cards[*i].draw_to == BoardZone::Kaiju);
+
+    if own_deck {
+        (others, kaiju)
+    } else {
+        (kaiju, others)
+    }
+}
+
+/// The options that look best for us
+pub fn choose(board: &BoardState, options: &[Mode], picks: usize) -> Vec<usize> {
+    let mut ranked: Vec<usize> = (0..options.len()).collect();
+    ranked.sort_by_key(|idx| -effects_value(board, &options[*idx].effects));
+    ranked.truncate(picks);
+    ranked
+}
+
+/// The cheapest building or card in hand, or the most dangerous kaiju
+pub fn pay_extra_cost(board: &BoardState, extra_cost: ExtraCost, options: &[usize]) -> usize {
+    let best = match extra_cost {
+        ExtraCost::Sacrifice => options.iter().min_by_key(|idx| board.buildings.cards[**idx].cost.count),
+        ExtraCost::Discard => options.iter().min_by_key(|idx| board.hand.cards[**idx].cost.count),
+        ExtraCost::ReturnKaiju => options.iter().max_by_key(|idx| board.kaiju_zone.cards[**idx].threat()),
+    };
+
+    *best.expect("Nothing to pay the extra cost with")
+}
+
+/// How good the board is for its player: points, buildings and cards, minus kaiju.
+/// Kaiju still in the deck count too, or keeping a full hand (and drawing nothing) would look good.
+pub fn evaluate(board: &BoardState) -> i16 {
+    let kaiju: i16 = board.kaiju_zone.cards.iter()
+        .chain(board.deck.iter().filter(|c| c.draw_to == BoardZone::Kaiju))
+        .map(kaiju_danger)
+        .sum();
+    let cards: i16 = board.deck.iter()
+        .chain(board.hand.cards.iter())
+        .filter(|c| c.draw_to != BoardZone::Kaiju)
+        .map(|c| buy_value(board, c).max(0))
+        .sum();
+
+    2 * board.score() + BUILDING_VALUE * board.buildings.cards.len() as i16 - kaiju + cards / 4
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game_objects::fixtures::*;
+
+    fn reacting(name: &str, counter: bool, effects: Vec<Effect>, on_play: Vec<Effect>) -> Card {
+        let reaction = Response{ trigger: Trigger::Play, tag: None, counter, effects, times: None };
+        Card{ reaction: Some(reaction), on_play, ..named(name) }
+    }
+
+    fn played(cost: i16) -> StackItem {
+        let card = Card{ cost: Cost{ currency: Globals::Build, count: cost }, ..named("played") };
+        StackItem::new(Trigger::Play, BoardZone::Hand, card, Vec::new())
+    }
+
+    #[test]
+    fn counters_the_expensive_card() {
+        let hand = vec!(reacting("cheer", false, vec!(Effect::Points{val: 1}), Vec::new()),
+                        reacting("deny", true, Vec::new(), Vec::new()));
+        let board = board(hand, Vec::new(), Vec::new());
+
+        assert_eq!(react(&board, &played(4), &[0, 1]), Some(1));
+    }
+
+    #[test]
+    fn keeps_the_card_that_is_better_played() {
+        let hand = vec!(reacting("cheer", false, vec!(Effect::Points{val: 1}), vec!(Effect::Points{val: 3})));
+        let board = board(hand, Vec::new(), Vec::new());
+
+        assert_eq!(react(&board, &played(0), &[0]), None);
+    }
+
+    fn interceptor(intercepts_left: u8) -> Card {
+        Card{ intercept: Some(Intercept{ tag: Tag::Sea, times: 2 }), intercepts_left, ..named("kaiju") }
+    }
+
+    fn playing(card: Card) -> StackItem {
+        StackItem::new(Trigger::Play, BoardZone::Hand, card.clone(), card.on_play)
+    }
+
+    #[test]
+    fn intercepts_the_card_that_helps_its_player() {
+        let board = board(Vec::new(), Vec::new(), Vec::new());
+        let card = Card{ on_play: vec!(Effect::Points{val: 2}), ..named("parade") };
+
+        assert!(intercept(&board, &interceptor(1), &playing(card)));
+    }
+
+    #[test]
+    fn lets_through_the_card_that_helps_the_owner() {
+        let board = board(Vec::new(), Vec::new(), Vec::new());
+        let sent = Card{ on_play: vec!(Effect::Points{val: 2}), give_to_enemy: true, ..named("sent") };
+        let harmful = Card{ on_play: vec!(Effect::Break), ..named("harmful") };
+
+        assert!(!intercept(&board, &interceptor(2), &playing(sent)));
+        assert!(!intercept(&board, &interceptor(2), &playing(harmful)));
+    }
+
+    #[test]
+    fn keeps_the_last_intercept_for_something_better() {
+        let board = board(Vec::new(), Vec::new(), Vec::new());
+        let trifle = Card{ on_play: vec!(Effect::Global{key: Globals::Build, val: 1}), ..named("trifle") };
+
+        assert!(!intercept(&board, &interceptor(1), &playing(trifle.clone())));
+        assert!(intercept(&board, &interceptor(2), &playing(trifle)));
+    }
+
+    fn playing_for(val: i16) -> Card {
+        Card{ on_play: vec!(Effect::Points{val}), ..named("card") }
+    }
+
+    #[test]
+    fn plays_the_most_helpful_card() {
+        let mut board = board(vec!(playing_for(1), playing_for(3), playing_for(-2)), Vec::new(), Vec::new());
+        board.start_turn();
+
+        assert!(matches!(decide(&board).action, GameEvent::CardPicked(1)));
+    }
+
+    #[test]
+    fn ends_the_turn_when_nothing_helps() {
+        let mut board = board(vec!(playing_for(-2)), Vec::new(), Vec::new());
+        board.start_turn();
+
+        assert!(matches!(decide(&board).action, GameEvent::EndTurn));
+    }
+
+    #[test]
+    fn targets_the_most_dangerous_kaiju() {
+        let killer = Card{ target_zone: BoardZone::Kaiju, target_effect: TargetEffect::Kill, ..named("killer") };
+        let small = Card{ on_strike: vec!(Effect::Break), ..named("small") };
+        let big = Card{ on_strike: vec!(Effect::BreakEverything), ..named("big") };
+        let board = board(vec!(killer), Vec::new(), vec!(small, big));
+
+        assert!(matches!(target_card(&board, 0, BoardZone::Kaiju), GameEvent::CardTargeted(BoardZone::Hand, 0, BoardZone::Kaiju, 1)));
+    }
+}
@@ -0,0 +1,54 @@
+use crate::automaton::*;
+use crate::game_logic::BoardState;
+use crate::observation::Observation;
+use super::{Strategy, Explanation, candidate_actions, play_greedily};
+use super::greedy::{evaluate, kaiju_danger};
+
+/// Tries every action on a copy of the board, searching `depth` actions ahead and finishing the turn greedily,
+/// and picks the one leaving the board in the best shape.
+#[derive(Debug)]
+pub struct Lookahead {
+    pub depth: u8,
+}
+
+impl Strategy for Lookahead {
+    fn explain(&self, board: &Observation) -> Explanation {
+        let candidates: Vec<(GameEvent, i16)> = candidate_actions(board).into_iter()
+            .map(|action| {
+                let value = value_after(board, &action, self.depth);
+                (action, value)
+            })
+            .collect();
+
+        let (action, value) = candidates.iter()
+            .max_by_key(|(_, value)| *value)
+            .cloned()
+            .unwrap_or((GameEvent::EndTurn, 0));
+
+        Explanation::new(
+            action,
+            format!("leaves the best board ({}) searching {} deep, then playing greedily", value, self.depth),
+            candidates.into_iter().map(|(action, value)| (action, value as f32)).collect()
+        )
+    }
+}
+
+fn value_after(board: &BoardState, action: &GameEvent, depth: u8) -> i16 {
+    let mut sim = board.simulation();
+    let bonus = sim.apply_action(action)
+        .map_or(0, |kaiju| 2 * kaiju_danger(&kaiju)); // sent to the opponent
+
+    if let GameEvent::EndTurn = action {
+        return bonus + evaluate(&sim);
+    }
+
+    if depth > 1 {
+        let best = candidate_actions(&sim).iter()
+            .map(|next| value_after(&sim, next, depth - 1))
+            .max()
+            .unwrap_or_else(|| evaluate(&sim));
+        return bonus + best;
+    }
+
+    bonus + play_greedily(&mut sim) + evaluate(&sim)
+}
@@ -0,0 +1,196 @@
+use crate::automaton::*;
+use crate::game_logic::BoardState;
+use crate::game_objects::Card;
+use crate::observation::Observation;
+use super::{Strategy, Explanation, candidate_actions, play_turn_greedily, same_action};
+use super::greedy::{evaluate, kaiju_danger};
+use std::time::{Duration, Instant};
+
+/// How much to prefer trying actions seldom tried over the best ones so far, in units of board value
+const EXPLORATION: f32 = 5.0;
+
+/// Monte Carlo tree search over actions of our turn.
+///
+/// We don't know the order of our deck (except for cards we looked at), the store decks, or the opponent's hand
+/// and deck, so every iteration plays on a copy of the game where they are made up (a determinization). The tree
+/// follows the actions legal in that copy; the rest of the turn is played greedily, then the opponent's turn,
+/// and `horizon` further rounds. Then our board is evaluated. The action tried most often wins.
+#[derive(Debug)]
+pub struct Mcts {
+    pub iterations: u32,
+    pub millis: Option<u64>,
+    pub horizon: u8,
+}
+
+impl Strategy for Mcts {
+    fn explain(&self, board: &Observation) -> Explanation {
+        let started = Instant::now();
+        let deadline = self.millis.map(Duration::from_millis);
+        let mut root = Node::new(GameEvent::Started);
+
+        for _ in 0..self.iterations {
+            if deadline.is_some_and(|limit| started.elapsed() > limit) {
+                break;
+            }
+            let (mut sim, mut opponents) = board.determinize();
+            self.iterate(&mut root, &mut sim, opponents.first_mut(), 0.0);
+        }
+
+        let candidates = root.children.iter()
+            .map(|child| (child.action.clone(), child.mean()))
+            .collect();
+
+        match root.children.into_iter().max_by_key(|child| child.visits) {
+            Some(best) => {
+                let reason = format!("tried most often ({} of {} times), worth {:.1} on average", best.visits, root.visits, best.mean());
+                Explanation::new(best.action, reason, candidates)
+            },
+            None => Explanation::new(GameEvent::EndTurn, "nothing to try", candidates)
+        }
+    }
+}
+
+impl Mcts {
+    /// Walks down the tree (expanding it by one action), plays the rest out and returns the result.
+    /// `sent` is the value of kaiju sent to the opponent on the way here.
+    fn iterate(&self, node: &mut Node, sim: &mut BoardState, mut opponent: Option<&mut BoardState>, sent: f32) -> f32 {
+        let legal = candidate_actions(sim);
+
+        let untried = legal.iter().find(|action| !node.children.iter().any(|c| same_action(&c.action, action))).cloned();
+        let reward = match untried {
+            Some(action) => {
+                let mut child = Node::new(action);
+                let reward = sent + self.play_out(&child.action, sim, opponent);
+                child.update(reward);
+                node.children.push(child);
+                reward
+            },
+            None => {
+                let parent_visits = node.visits;
+                let child = node.children.iter_mut()
+                    .filter(|c| legal.iter().any(|action| same_action(&c.action, action)))
+                    .max_by(|a, b| a.ucb(parent_visits).partial_cmp(&b.ucb(parent_visits)).unwrap())
+                    .expect("Nothing to do, not even ending the turn");
+
+                if let GameEvent::EndTurn = child.action {
+                    let reward = sent + self.play_out(&child.action, sim, opponent);
+                    child.update(reward);
+                    reward
+                } else {
+                    let given = sim.apply_action(&child.action).into_iter().collect();
+                    let sent = sent + send(given, opponent.as_deref_mut());
+                    self.iterate(child, sim, opponent, sent)
+                }
+            }
+        };
+
+        node.update(reward);
+        reward
+    }
+
+    /// Does the action, then finishes the turn greedily. The opponent plays their turn next, then `horizon`
+    /// further rounds are played, all greedily. Returns value of the result.
+    fn play_out(&self, action: &GameEvent, sim: &mut BoardState, mut opponent: Option<&mut BoardState>) -> f32 {
+        let mut given: Vec<Card> = sim.apply_action(action).into_iter().collect();
+        if !same_action(action, &GameEvent::EndTurn) {
+            given.extend(play_turn_greedily(sim));
+        }
+        let mut sent = send(given, opponent.as_deref_mut());
+
+        for round in 0..=self.horizon {
+            if let Some(opponent) = opponent.as_deref_mut() {
+                if sim.is_defeated() || opponent.is_defeated() {
+                    break;
+                }
+                opponent.start_turn();
+                let given = play_turn_greedily(opponent);
+                send(given, Some(sim));
+            }
+
+            if round == self.horizon || sim.is_defeated() {
+                break;
+            }
+            sim.start_turn();
+            let given = play_turn_greedily(sim);
+            sent += send(given, opponent.as_deref_mut());
+        }
+
+        sent + evaluate(sim) as f32
+    }
+}
+
+#[derive(Debug)]
+struct Node {
+    action: GameEvent, // that led here
+    visits: u32,
+    total: f32, // sum of rewards of the visits
+    children: Vec<Node>,
+}
+
+impl Node {
+    fn new(action: GameEvent) -> Self {
+        Self{ action, visits: 0, total: 0.0, children: Vec::new() }
+    }
+
+    fn update(&mut self, reward: f32) {
+        self.visits += 1;
+        self.total += reward;
+    }
+
+    fn mean(&self) -> f32 {
+        self.total / self.visits as f32
+    }
+
+    /// Upper confidence bound of the node's value
+    fn ucb(&self, parent_visits: u32) -> f32 {
+        self.mean() + EXPLORATION * ((parent_visits as f32).ln() / self.visits as f32).sqrt()
+    }
+}
+
+/// Gives the kaiju to the opponent of the board that sent them, if there is one. Returns their value for the sender.
+fn send(given: Vec<Card>, to: Option<&mut BoardState>) -> f32 {
+    let value = given.iter().map(|kaiju| 2.0 * kaiju_danger(kaiju) as f32).sum();
+    if let Some(to) = to {
+        for mut kaiju in given {
+            kaiju.owner = Some(to.player.opponent_idx);
+            to.receive(kaiju);
+        }
+    }
+    value
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game_objects::{Effect, Placement, BoardZone};
+    use crate::game_objects::fixtures::*;
+
+    #[test]
+    fn rollout_plays_the_opponent_turns_in_between() {
+        for horizon in 0..3 {
+            let mcts = Mcts{ iterations: 1, millis: None, horizon };
+            let mut sim = board(Vec::new(), vec!(named("house")), Vec::new());
+            let mut opponent = board(Vec::new(), vec!(named("house")), Vec::new());
+            sim.turn = 1;
+            opponent.turn = 1;
+
+            mcts.play_out(&GameEvent::EndTurn, &mut sim, Some(&mut opponent));
+
+            assert_eq!(sim.turn, 2 + horizon as u16);
+            assert_eq!(opponent.turn, 2 + horizon as u16);
+        }
+    }
+
+    #[test]
+    fn kaiju_sent_in_a_rollout_reach_the_opponent() {
+        let mut opponent = board(Vec::new(), vec!(named("house")), Vec::new());
+        opponent.player.opponent_idx = 0;
+        let kaiju = Card{ on_strike: vec!(Effect::Break), placement: Placement::Kaiju, draw_to: BoardZone::Kaiju, ..named("kaiju") };
+
+        let value = send(vec!(kaiju), Some(&mut opponent));
+
+        assert_eq!(value, 6.0);
+        assert_eq!(names(&opponent.kaiju_zone.cards), ["kaiju"]);
+        assert_eq!(opponent.kaiju_zone.cards[0].owner, Some(0));
+    }
+}
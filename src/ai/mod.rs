@@ -0,0 +1,302 @@
+use crate::automaton::*;
+use crate::game_objects::*;
+use crate::game_logic::BoardState;
+use crate::observation::Observation;
+use serde_derive::*;
+use std::collections::HashMap;
+
+mod greedy;
+mod random;
+mod lookahead;
+mod mcts;
+mod scripted;
+
+use greedy::{Greedy, kaiju_danger};
+use random::Random;
+use lookahead::Lookahead;
+use mcts::Mcts;
+use scripted::Scripted;
+pub use scripted::Personality;
+
+/// How an AI player makes its decisions. Every method gets what the AI player knows, mostly its own board;
+/// the real boards are out of its reach.
+///
+/// Only the turn itself has to be played by each strategy; other decisions default to the greedy heuristics.
+pub trait Strategy {
+    /// Picks the next thing to do in our turn, and tells why. Has to end the turn eventually.
+    fn explain(&self, board: &Observation) -> Explanation;
+
+    fn select_card(&self, board: &Observation) -> GameEvent {
+        self.explain(board).action
+    }
+
+    fn target_card(&self, board: &Observation, card_idx: usize, card_target: BoardZone) -> GameEvent {
+        greedy::target_card(board, card_idx, card_target)
+    }
+
+    /// Pick a card from hand (one of the candidates) to react to the stack item with, or None to pass.
+    fn react(&self, board: &Observation, item: &StackItem, candidates: &[usize]) -> Option<usize> {
+        greedy::react(board, item, candidates)
+    }
+
+    /// Should our kaiju intercept the card the opponent is playing?
+    fn intercept(&self, board: &Observation, kaiju: &Card, item: &StackItem) -> bool {
+        greedy::intercept(board, kaiju, item)
+    }
+
+    /// Rearrange revealed top cards of a deck: returns indices of cards to keep on top (in order) and of cards to put
+    /// to the bottom.
+    fn arrange(&self, board: &Observation, cards: &[Card], own_deck: bool) -> (Vec<usize>, Vec<usize>) {
+        greedy::arrange(board, cards, own_deck)
+    }
+
+    /// Pick options of a modal effect
+    fn choose(&self, board: &Observation, options: &[Mode], picks: usize) -> Vec<usize> {
+        greedy::choose(board, options, picks)
+    }
+
+    /// Pick what to give up to play a card (one of the options)
+    fn pay_extra_cost(&self, board: &Observation, extra_cost: ExtraCost, options: &[usize]) -> usize {
+        greedy::pay_extra_cost(board, extra_cost, options)
+    }
+}
+
+/// Why the AI does what it does in its turn: the actions it considered, how it scored them, and why it picked the action.
+/// Scores can be compared only within one explanation.
+#[derive(Debug, Clone)]
+pub struct Explanation {
+    pub action: GameEvent,
+    pub reason: String,
+    pub candidates: Vec<(GameEvent, f32)>,
+}
+
+impl Explanation {
+    pub fn new(action: GameEvent, reason: impl Into<String>, candidates: Vec<(GameEvent, f32)>) -> Self {
+        Self{ action, reason: reason.into(), candidates }
+    }
+
+    /// The action and the reason in one line. Cards are described as they are on the board now.
+    pub fn summary(&self, board: &BoardState) -> String {
+        format!("{}: {}", describe_action(board, &self.action), self.reason)
+    }
+
+    /// The summary followed by all candidates, best first
+    pub fn describe(&self, board: &BoardState) -> String {
+        let mut candidates = self.candidates.clone();
+        candidates.sort_by(|(_, a), (_, b)| b.partial_cmp(a).unwrap_or(std::cmp::Ordering::Equal));
+
+        let mut text = self.summary(board);
+        for (action, score) in candidates {
+            text.push_str(&format!("\n  {:>7.1}  {}", score, describe_action(board, &action)));
+        }
+        text
+    }
+}
+
+/// What the action does, in words
+pub fn describe_action(board: &BoardState, action: &GameEvent) -> String {
+    let hand_card = |idx: &usize| board.hand.cards.get(*idx).map_or("?".to_string(), |c| c.name.clone());
+
+    match action {
+        GameEvent::CardPicked(idx) => format!("play {}", hand_card(idx)),
+        GameEvent::CardTargeted(_, idx, BoardZone::Kaiju, target) => format!("play {} on {}", hand_card(idx),
+            board.kaiju_zone.cards.get(*target).map_or("?".to_string(), |c| c.name.clone())),
+        GameEvent::CardBought(zone, idx) => format!("buy {}", board.stores.iter()
+            .find(|s| s.menu.zone == *zone)
+            .and_then(|s| s.menu.cards.get(*idx))
+            .map_or("?".to_string(), |c| c.name.clone())),
+        GameEvent::CardActivated(idx) => format!("use {}",
+            board.buildings.cards.get(*idx).map_or("?".to_string(), |c| c.name.clone())),
+        GameEvent::EndTurn => "end turn".to_string(),
+        other => format!("{:?}", other)
+    }
+}
+
+/// What happens with explanations of AI decisions, as set in game data
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(default)]
+pub struct ExplanationSettings {
+    pub log_file: Option<String>, // explanations are appended there
+    pub panel: bool, // show the decisions of the AI opponent on screen
+}
+
+/// How fast AI players play in the UI, as set in game data. Simulations always play instantly.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct AiPacing {
+    pub millis: u64, // each decision is shown this long before it happens; 0 plays instantly
+}
+
+const MIN_PACING_MILLIS: u64 = 50;
+const MAX_PACING_MILLIS: u64 = 5000;
+
+impl AiPacing {
+    pub fn faster(&mut self) {
+        self.millis = (self.millis / 2).max(MIN_PACING_MILLIS);
+    }
+
+    pub fn slower(&mut self) {
+        self.millis = (self.millis * 2).clamp(MIN_PACING_MILLIS, MAX_PACING_MILLIS);
+    }
+}
+
+impl Default for AiPacing {
+    fn default() -> Self {
+        Self{ millis: 800 }
+    }
+}
+
+/// Strategy of an AI player and its parameters, as set for the player in game data
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(tag = "strategy")]
+pub enum AiConfig {
+    Random,
+    #[default]
+    Greedy,
+    Lookahead{#[serde(default = "one")] depth: u8}, // number of actions searched before finishing the turn greedily
+    Mcts{
+        #[serde(default = "default_iterations")]
+        iterations: u32,
+        #[serde(default)]
+        millis: Option<u64>, // time limit for a decision, if the iterations take too long
+        #[serde(default)]
+        horizon: u8, // our further turns simulated after this one
+    },
+    Scripted{personality: String}, // name of a personality from the game data
+}
+
+fn one() -> u8 {
+    1
+}
+
+fn default_iterations() -> u32 {
+    300
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct AI {
+    config: AiConfig,
+    #[serde(default)]
+    personality: Option<Personality>, // the one the scripted strategy follows
+}
+
+impl AI {
+    /// Personalities are those from the game data; a scripted AI has to follow one of them
+    pub fn new(config: AiConfig, personalities: &HashMap<String, Personality>) -> Box<Self> {
+        let personality = match &config {
+            AiConfig::Scripted{personality} => Some(personalities.get(personality)
+                .unwrap_or_else(|| panic!("Unknown AI personality {}", personality))
+                .clone()),
+            _ => None
+        };
+        Box::new(Self{ config, personality })
+    }
+
+    pub fn strategy(&self) -> Box<dyn Strategy> {
+        match self.config {
+            AiConfig::Random => Box::new(Random),
+            AiConfig::Greedy => Box::new(Greedy),
+            AiConfig::Lookahead{depth} => Box::new(Lookahead{ depth }),
+            AiConfig::Mcts{iterations, millis, horizon} => Box::new(Mcts{ iterations, millis, horizon }),
+            AiConfig::Scripted{..} => Box::new(Scripted{ personality: self.personality.clone().unwrap_or_default() }),
+        }
+    }
+}
+
+/// Legal actions worth considering: free cards are left out, as they could be bought forever
+fn candidate_actions(board: &BoardState) -> Vec<GameEvent> {
+    board.legal_actions().into_iter()
+        .filter(|action| match action {
+            GameEvent::CardBought(zone, idx) => board.stores.iter()
+                .find(|s| s.menu.zone == *zone)
+                .is_some_and(|s| s.menu.cards[*idx].effective_cost().count > 0),
+            _ => true
+        })
+        .collect()
+}
+
+/// Plays the rest of the turn on a simulated board greedily. Returns value of kaiju sent to the opponent meanwhile.
+fn play_greedily(sim: &mut BoardState) -> i16 {
+    play_turn_greedily(sim).iter()
+        .map(|kaiju| 2 * kaiju_danger(kaiju))
+        .sum()
+}
+
+/// Plays the rest of the turn on a simulated board greedily. Returns kaiju sent to the opponent meanwhile.
+fn play_turn_greedily(sim: &mut BoardState) -> Vec<Card> {
+    const MAX_ACTIONS: usize = 50; // the turn should end much sooner
+
+    let mut sent = Vec::new();
+    for _ in 0..MAX_ACTIONS {
+        let action = greedy::decide(sim).action;
+        sent.extend(sim.apply_action(&action));
+        if let GameEvent::EndTurn = action {
+            break;
+        }
+    }
+
+    sent
+}
+
+/// Whether the actions are the same; GameEvent can't be compared in general
+pub fn same_action(a: &GameEvent, b: &GameEvent) -> bool {
+    match (a, b) {
+        (GameEvent::CardPicked(x), GameEvent::CardPicked(y)) => x == y,
+        (GameEvent::CardTargeted(zone_a, x, target_zone_a, target_a), GameEvent::CardTargeted(zone_b, y, target_zone_b, target_b)) =>
+            zone_a == zone_b && x == y && target_zone_a == target_zone_b && target_a == target_b,
+        (GameEvent::CardBought(zone_a, x), GameEvent::CardBought(zone_b, y)) => zone_a == zone_b && x == y,
+        (GameEvent::CardActivated(x), GameEvent::CardActivated(y)) => x == y,
+        (GameEvent::EndTurn, GameEvent::EndTurn) => true,
+        _ => false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game_objects::fixtures::*;
+
+    #[test]
+    fn strategy_parameters_default_when_left_out() {
+        let lookahead: AiConfig = serde_json::from_str(r#"{"strategy": "Lookahead"}"#).unwrap();
+        assert!(matches!(lookahead, AiConfig::Lookahead{depth: 1}));
+
+        let mcts: AiConfig = serde_json::from_str(r#"{"strategy": "Mcts", "millis": 50}"#).unwrap();
+        assert!(matches!(mcts, AiConfig::Mcts{iterations: 300, millis: Some(50), horizon: 0}));
+    }
+
+    #[test]
+    fn every_strategy_plays_its_turn_to_the_end() {
+        let configs = vec!(AiConfig::Random, AiConfig::Greedy, AiConfig::Lookahead{depth: 2},
+                           AiConfig::Mcts{iterations: 20, millis: None, horizon: 0});
+        for config in configs {
+            seed_rng(5);
+            let hand = vec!(Card{ on_play: vec!(Effect::Points{val: 1}), ..named("parade") }, named("dud"));
+            let mut board = board(hand, vec!(named("house")), Vec::new());
+            let ai = AI::new(config.clone(), &HashMap::new());
+
+            let ended = (0..10).any(|_| {
+                board.start_turn();
+                let action = ai.strategy().select_card(&Observation::of_board(&board));
+                board.apply_action(&action);
+                matches!(action, GameEvent::EndTurn)
+            });
+            assert!(ended, "{:?} did not end its turn", config);
+        }
+    }
+
+    #[test]
+    fn pacing_stays_within_bounds() {
+        let mut pacing = AiPacing{ millis: 0 };
+        pacing.slower();
+        assert_eq!(pacing.millis, MIN_PACING_MILLIS);
+
+        pacing.millis = 3000;
+        pacing.slower();
+        assert_eq!(pacing.millis, MAX_PACING_MILLIS);
+
+        pacing.millis = 80;
+        pacing.faster();
+        assert_eq!(pacing.millis, MIN_PACING_MILLIS);
+    }
+}
@@ -0,0 +1,28 @@
+use rand::seq::SliceRandom;
+use crate::automaton::*;
+use crate::game_objects::*;
+use crate::observation::Observation;
+use super::{Strategy, Explanation, candidate_actions};
+
+/// Does anything it can. Useful as a baseline for other strategies.
+#[derive(Debug)]
+pub struct Random;
+
+impl Strategy for Random {
+    fn explain(&self, board: &Observation) -> Explanation {
+        let actions = candidate_actions(board);
+        let action = with_rng(|rng| actions.choose(rng).cloned())
+            .unwrap_or(GameEvent::EndTurn);
+
+        Explanation::new(action, format!("picked at random from {} actions", actions.len()), Vec::new())
+    }
+
+    fn target_card(&self, board: &Observation, card_idx: usize, card_target: BoardZone) -> GameEvent {
+        let targets = board.payable_targets(card_idx);
+
+        match with_rng(|rng| targets.choose(rng).cloned()) {
+            Some(idx) => GameEvent::CardTargeted(BoardZone::Hand, card_idx, card_target, idx),
+            None => GameEvent::CardTargeted(BoardZone::None, 0, BoardZone::None, 0) // nothing to target
+        }
+    }
+}
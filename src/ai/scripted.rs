@@ -0,0 +1,113 @@
+use crate::game_objects::*;
+use crate::game_logic::BoardState;
+use crate::observation::Observation;
+use super::{Strategy, Explanation};
+use super::greedy::{Preferences, decide_with, play_value, target_value, buy_value};
+use serde_derive::*;
+use std::collections::HashMap;
+
+/// Themed behaviour of an AI player, written by card designers in game data (the "personalities" node).
+/// Cards are referred to by their names. What the personality doesn't say is decided greedily.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct Personality {
+    pub buy_first: Vec<String>, // bought whenever we can afford them, the first one that is in the store
+    pub never_buy: Vec<String>,
+    pub save_up: HashMap<Globals, i16>, // don't buy with the currency until we have at least this much of it
+    pub aggression: i16, // percent of the usual value of kaiju sent to the opponent
+    pub defense: i16, // percent of the usual value of killing, stunning and bouncing our kaiju
+}
+
+impl Default for Personality {
+    fn default() -> Self {
+        Self {
+            buy_first: Vec::new(),
+            never_buy: Vec::new(),
+            save_up: HashMap::new(),
+            aggression: 100,
+            defense: 100,
+        }
+    }
+}
+
+/// Plays like Greedy, as the personality says
+#[derive(Debug)]
+pub struct Scripted {
+    pub personality: Personality,
+}
+
+impl Strategy for Scripted {
+    fn explain(&self, board: &Observation) -> Explanation {
+        decide_with(board, self)
+    }
+}
+
+impl Preferences for Scripted {
+    fn play_value(&self, board: &BoardState, idx: usize) -> Option<i16> {
+        let defense = target_value(board, idx)? * (self.personality.defense - 100) / 100;
+        Some(play_value(board, idx)? + defense)
+    }
+
+    fn buy_value(&self, board: &BoardState, card: &Card) -> Option<i16> {
+        let cost = card.effective_cost();
+        let saved = self.personality.save_up.get(&cost.currency).cloned().unwrap_or(0);
+        if self.personality.never_buy.contains(&card.name) || board.globals.get(cost.currency) < saved {
+            return None;
+        }
+
+        let value = buy_value(board, card);
+        Some(if card.give_to_enemy { value * self.personality.aggression / 100 } else { value })
+    }
+
+    fn buy_priority(&self, card: &Card) -> Option<usize> {
+        self.personality.buy_first.iter().position(|name| *name == card.name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ai::greedy::Greedy;
+    use crate::automaton::GameEvent;
+    use crate::game_objects::fixtures::*;
+
+    fn costing(name: &str, count: i16, points: i16) -> Card {
+        Card{ cost: Cost{ currency: Globals::Build, count }, points, ..named(name) }
+    }
+
+    fn shopping(build: i16) -> BoardState {
+        let menu = CardContainer{ zone: BoardZone::BuildStore, cards: vec!(costing("tower", 2, 3), costing("hut", 1, 1)), size: None };
+        let mut board = BoardState{ stores: Box::new(vec!(Store{ menu, ..Default::default() })), ..board(Vec::new(), Vec::new(), Vec::new()) };
+        board.globals.add(Globals::Build, build);
+        board
+    }
+
+    fn following(personality: Personality) -> Scripted {
+        Scripted{ personality }
+    }
+
+    #[test]
+    fn buys_what_the_personality_likes_first() {
+        let builder = following(Personality{ buy_first: vec!("hut".to_string()), ..Default::default() });
+
+        assert!(matches!(decide_with(&shopping(5), &Greedy).action, GameEvent::CardBought(BoardZone::BuildStore, 0)));
+        assert!(matches!(decide_with(&shopping(5), &builder).action, GameEvent::CardBought(BoardZone::BuildStore, 1)));
+    }
+
+    #[test]
+    fn never_buys_what_it_shuns() {
+        let picky = following(Personality{ never_buy: vec!("tower".to_string()), ..Default::default() });
+
+        assert!(matches!(decide_with(&shopping(5), &picky).action, GameEvent::CardBought(BoardZone::BuildStore, 1)));
+    }
+
+    #[test]
+    fn saves_up_before_buying() {
+        let mut save_up = HashMap::new();
+        save_up.insert(Globals::Build, 4);
+        let saver = following(Personality{ save_up, ..Default::default() });
+
+        assert!(matches!(decide_with(&shopping(3), &saver).action, GameEvent::EndTurn));
+        assert!(matches!(decide_with(&shopping(4), &saver).action, GameEvent::CardBought(..)));
+    }
+}
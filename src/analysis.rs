@@ -0,0 +1,218 @@
+//! Looking back at a finished game: decisions of the players are scored again by a stronger AI,
+//! and those that cost the most are shown as mistakes.
+
+use crate::automaton::GameEvent;
+use crate::game_logic::BoardState;
+use crate::game_objects::{Effect, Globals};
+use crate::observation::Observation;
+use crate::ai::{AI, describe_action, same_action};
+use std::collections::VecDeque;
+use std::fmt;
+use std::time::Instant;
+
+/// Something a player did in their turn, and the boards when they did it
+#[derive(Debug, Clone)]
+pub struct RecordedDecision {
+    pub player: usize,
+    pub round: i16,
+    pub boards: Vec<BoardState>, // what the player knew is worked out only when the decision is analysed
+    pub action: GameEvent,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MistakeKind {
+    WrongBuy,
+    UnblockedStrike, // ended the turn with kaiju about to break buildings, when something could be done about it
+    WastedResources, // ended the turn when there was still something worth doing
+    WeakerPlay,
+}
+
+#[derive(Debug, Clone)]
+pub struct Mistake {
+    pub player: usize,
+    pub round: i16,
+    pub kind: MistakeKind,
+    pub done: String,
+    pub better: String,
+    pub cost: f32, // how much worse than the better action, as the analyst scores it
+}
+
+impl fmt::Display for Mistake {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let what = match self.kind {
+            MistakeKind::WrongBuy => "wrong buy",
+            MistakeKind::UnblockedStrike => "unblocked strike",
+            MistakeKind::WastedResources => "wasted resources",
+            MistakeKind::WeakerPlay => "weaker play",
+        };
+        write!(f, "Round {}, {}: {}; better {} (by {:.1})", self.round + 1, what, self.done, self.better, self.cost)
+    }
+}
+
+/// Decisions of some players being scored by the analyst, a few at a time
+#[derive(Debug)]
+pub struct Analysis {
+    analyst: Box<AI>,
+    pending: VecDeque<RecordedDecision>, // in the order they were made
+    mistakes: Vec<Mistake>,
+    players: Vec<usize>,
+    deadline: Instant, // decisions still pending then are not looked at
+}
+
+impl Analysis {
+    pub fn new(decisions: Vec<RecordedDecision>, players: Vec<usize>, analyst: Box<AI>, deadline: Instant) -> Self {
+        let pending = decisions.into_iter().filter(|decision| players.contains(&decision.player)).collect();
+        Self{ analyst, pending, mistakes: Vec::new(), players, deadline }
+    }
+
+    pub fn players(&self) -> &[usize] {
+        &self.players
+    }
+
+    /// Scores pending decisions until the time given (or the deadline) passes. Returns whether the analysis is over.
+    pub fn advance(&mut self, until: Instant) -> bool {
+        let until = until.min(self.deadline);
+        while Instant::now() < until {
+            let decision = match self.pending.pop_front() {
+                Some(decision) => decision,
+                None => break
+            };
+            self.mistakes.extend(find_mistake(&decision, &self.analyst));
+        }
+        self.is_over()
+    }
+
+    pub fn is_over(&self) -> bool {
+        self.pending.is_empty() || Instant::now() >= self.deadline
+    }
+
+    /// Costly decisions of the player found so far, the most costly first
+    pub fn mistakes(&self, player: usize) -> Vec<&Mistake> {
+        let mut mistakes: Vec<&Mistake> = self.mistakes.iter().filter(|m| m.player == player).collect();
+        mistakes.sort_by(|a, b| b.cost.partial_cmp(&a.cost).unwrap_or(std::cmp::Ordering::Equal));
+        mistakes
+    }
+}
+
+/// Scores the decision with the analyst, as the player could see the game; it is a mistake if something else scores better
+fn find_mistake(decision: &RecordedDecision, analyst: &AI) -> Option<Mistake> {
+    let observation = Observation::new(&decision.boards, decision.player);
+    let explanation = analyst.strategy().explain(&observation);
+    let chosen = chosen_score(&explanation.candidates, &decision.action)?;
+    let (better, best) = explanation.candidates.iter()
+        .max_by(|(_, a), (_, b)| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal))?;
+    if best - chosen <= 0.0 {
+        return None;
+    }
+
+    let board: &BoardState = &observation;
+    let (kind, done) = classify(board, &decision.action);
+    Some(Mistake {
+        player: decision.player,
+        round: decision.round,
+        kind,
+        done,
+        better: describe_action(board, better),
+        cost: best - chosen,
+    })
+}
+
+/// Score of the action among those the analyst considered. Strategies that target cards themselves
+/// only consider playing the card.
+fn chosen_score(candidates: &[(GameEvent, f32)], action: &GameEvent) -> Option<f32> {
+    let exact = candidates.iter().find(|(candidate, _)| same_action(candidate, action));
+    let played = candidates.iter().find(|(candidate, _)| match (candidate, action) {
+        (GameEvent::CardPicked(x), GameEvent::CardTargeted(_, y, _, _)) => x == y,
+        _ => false
+    });
+    exact.or(played).map(|(_, score)| *score)
+}
+
+fn classify(board: &BoardState, action: &GameEvent) -> (MistakeKind, String) {
+    match action {
+        GameEvent::CardBought(..) => (MistakeKind::WrongBuy, describe_action(board, action)),
+        GameEvent::EndTurn => {
+            let strikers = unblocked_strikers(board);
+            if !strikers.is_empty() {
+                return (MistakeKind::UnblockedStrike, format!("ended the turn with {} striking", strikers.join(", ")));
+            }
+
+            let unspent: Vec<String> = Globals::in_game().into_iter()
+                .filter(|currency| *currency != Globals::Block)
+                .map(|currency| (currency, board.globals.get(currency)))
+                .filter(|(_, value)| *value > 0)
+                .map(|(currency, value)| format!("{} {}", value, currency))
+                .collect();
+            if unspent.is_empty() {
+                (MistakeKind::WeakerPlay, "ended the turn".to_string())
+            } else {
+                (MistakeKind::WastedResources, format!("ended the turn with {} unspent", unspent.join(", ")))
+            }
+        },
+        _ => (MistakeKind::WeakerPlay, describe_action(board, action)),
+    }
+}
+
+/// Kaiju that will break our buildings when the turn ends, as far as our blocks go
+fn unblocked_strikers(board: &BoardState) -> Vec<String> {
+    let mut blocks = board.globals.get(Globals::Block);
+    board.kaiju_zone.cards.iter()
+        .filter(|kaiju| !kaiju.stunned)
+        .filter(|kaiju| {
+            let mut breaks = false;
+            for effect in kaiju.strike_effects() {
+                match effect {
+                    Effect::Break if blocks > 0 => blocks -= 1,
+                    Effect::Break | Effect::BreakUnblockable | Effect::BreakEverything => breaks = true,
+                    _ => {}
+                }
+            }
+            breaks
+        })
+        .map(|kaiju| kaiju.name.clone())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ai::AiConfig;
+    use crate::game_objects::Card;
+    use crate::game_objects::fixtures::*;
+    use std::time::Duration;
+
+    fn ended_with_parade_in_hand(player: usize) -> RecordedDecision {
+        let parade = Card{ on_play: vec!(Effect::Points{val: 2}), ..named("parade") };
+        let mut boards = vec!(board(vec!(parade.clone()), vec!(named("house")), Vec::new()),
+                              board(vec!(parade), vec!(named("house")), Vec::new()));
+        boards[player].start_turn();
+        RecordedDecision{ player, round: 2, boards, action: GameEvent::EndTurn }
+    }
+
+    fn greedy() -> Box<AI> {
+        AI::new(AiConfig::Greedy, &Default::default())
+    }
+
+    #[test]
+    fn finds_mistakes_of_the_analysed_players_only() {
+        let decisions = vec!(ended_with_parade_in_hand(0), ended_with_parade_in_hand(1));
+        let deadline = Instant::now() + Duration::from_secs(60);
+        let mut analysis = Analysis::new(decisions, vec!(0), greedy(), deadline);
+
+        while !analysis.advance(Instant::now() + Duration::from_millis(10)) {}
+
+        let mistakes = analysis.mistakes(0);
+        assert_eq!(mistakes.len(), 1);
+        assert_eq!((mistakes[0].round, mistakes[0].kind), (2, MistakeKind::WeakerPlay));
+        assert_eq!(mistakes[0].better, "play parade");
+        assert!(analysis.mistakes(1).is_empty());
+    }
+
+    #[test]
+    fn nothing_is_analysed_after_the_deadline() {
+        let mut analysis = Analysis::new(vec!(ended_with_parade_in_hand(0)), vec!(0), greedy(), Instant::now());
+
+        assert!(analysis.advance(Instant::now() + Duration::from_secs(1)));
+        assert!(analysis.mistakes(0).is_empty());
+    }
+}
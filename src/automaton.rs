@@ -15,6 +15,13 @@ pub enum GameEvent {
     CardPicked(usize),
     CardTargeted(BoardZone, usize, BoardZone, usize),
     CardBought(BoardZone, usize),
+    CardActivated(usize), // building using its activated ability
+    CostPaid(usize, Option<(BoardZone, usize)>, usize), // card in hand, its target, card the extra cost is paid with
+    Reacted(usize, Option<usize>), // reacting board, card in its hand (None = pass)
+    InterceptDecided(usize, bool), // intercepting kaiju, whether its owner lets it intercept
+    Chosen(Vec<usize>), // options picked in a modal effect
+    Arranged(Vec<usize>, Vec<usize>), // revealed cards to put on top (in this order) and to the bottom of the deck
+    HintRequested, // the player on turn wants to know what their AI would do
     EndTurn, 
     StartTurn,
     IO(Event), // keyboard, mouse etc.
@@ -0,0 +1,100 @@
+//! Plays many games between AI players without a window and prints statistics about them.
+//!
+//! Usage: simulate [--cards FILE] [--games N] [--seed S] [--ai STRATEGY] [--ai STRATEGY]
+//!
+//! STRATEGY is a strategy name (Random, Greedy, Lookahead, Mcts), a personality from the card file (e.g. Builder)
+//! or its JSON config, e.g. '{"strategy": "Mcts", "iterations": 100}'. Players keep the strategies from the card file otherwise.
+//! Game number i is played with seed S + i; the players swap seats every game.
+
+use streaming_deckbuilder::ai::AiConfig;
+use streaming_deckbuilder::game_control::PlayerControl;
+use streaming_deckbuilder::game_objects::seed_rng;
+use streaming_deckbuilder::loading::*;
+use streaming_deckbuilder::simulation::{play_game, Statistics};
+use std::env;
+use std::fs;
+
+struct Options {
+    cards: String,
+    games: u64,
+    seed: u64,
+    strategies: Vec<AiConfig>,
+}
+
+fn parse_strategy(arg: &str) -> AiConfig {
+    if arg.trim_start().starts_with('{') {
+        return serde_json::from_str(arg).unwrap_or_else(|e| panic!("Unknown strategy {}: {}", arg, e));
+    }
+
+    // anything that isn't a strategy should be a personality
+    serde_json::from_str(&format!("{{\"strategy\": \"{}\"}}", arg))
+        .unwrap_or_else(|_| AiConfig::Scripted{ personality: arg.to_string() })
+}
+
+fn parse_options() -> Options {
+    let mut options = Options {
+        cards: "static/cards_expanded.json".to_string(),
+        games: 100,
+        seed: 0,
+        strategies: Vec::new(),
+    };
+
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().unwrap_or_else(|| panic!("Missing value of {}", arg));
+        match arg.as_str() {
+            "--cards" => options.cards = value(),
+            "--games" => options.games = value().parse().expect("Number of games should be a number"),
+            "--seed" => options.seed = value().parse().expect("Seed should be a number"),
+            "--ai" => options.strategies.push(parse_strategy(&value())),
+            _ => panic!("Unknown argument {}", arg)
+        }
+    }
+
+    options
+}
+
+fn main() {
+    let options = parse_options();
+
+    let file = fs::read(&options.cards).expect("Can't read the card file");
+    let json: serde_json::Value = serde_json::from_slice(&file).expect("file should be proper JSON");
+    let cards = load_cards(&json);
+
+    let mut players = load_players(&json);
+    for (idx, player) in players.iter_mut().enumerate() {
+        player.control = PlayerControl::AI;
+        if let Some(strategy) = options.strategies.get(idx) {
+            player.ai = strategy.clone();
+        }
+        player.name = format!("{} ({:?})", player.name, player.ai);
+    }
+
+    let mut statistics = Statistics::default();
+    for game in 0..options.games {
+        seed_rng(options.seed + game);
+
+        let mut seats = players.clone();
+        if game % 2 == 1 {
+            seats.reverse();
+        }
+        for (idx, player) in seats.iter_mut().enumerate() {
+            player.opponent_idx = (idx + 1) % 2;
+        }
+
+        let boards = seats.into_iter()
+            .map(|player| load_board(&json, &cards, player))
+            .collect();
+
+        let record = play_game(boards, load_victory(&json), load_event_deck(&json, &cards));
+        statistics.add(&record);
+
+        match record.winner {
+            Some(winner) => println!("Game {}: {} won after {} rounds", game + 1, record.players[winner], record.rounds),
+            None => println!("Game {}: draw after {} rounds", game + 1, record.rounds),
+        }
+    }
+
+    println!();
+    print!("{}", statistics);
+}
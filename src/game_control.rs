@@ -2,6 +2,9 @@ use crate::automaton::*;
 use crate::game_logic::{BoardState, GameplayState};
 use crate::ui::game_end_state::GameEndState;
 use crate::loading::Assets;
+use crate::game_objects::{Deck, Notice};
+use crate::ai::{AI, AiConfig, AiPacing, ExplanationSettings};
+use crate::analysis::RecordedDecision;
 use std::collections::HashMap;
 use std::mem::take;
 use std::hash::{Hash, Hasher};
@@ -25,6 +28,8 @@ pub struct Player {
    pub starting_deck: String,
    pub starting_buildings: String,
    pub control: PlayerControl,
+   #[serde(default)]
+   pub ai: AiConfig, // used only for AI control
    //opponent: Option<&Player>
 
    #[serde(skip)]
@@ -45,26 +50,121 @@ impl Hash for Player {
 
 impl Eq for Player {}
 
+/// Alternative ways for the game to end, besides all players but one being defeated.
+/// When the game ends this way, the player with the highest score wins.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct VictoryRules {
+    pub points: Option<i16>, // the game ends when someone reaches this score
+    pub rounds: Option<i16>, // the game ends after this many rounds
+}
+
+impl VictoryRules {
+    pub fn game_ended(&self, boards: &[BoardState], round: i16) -> bool {
+        let by_points = match self.points {
+            Some(points) => boards.iter().any(|b| b.score() >= points),
+            None => false
+        };
+        let by_rounds = match self.rounds {
+            Some(rounds) => round >= rounds,
+            None => false
+        };
+
+        by_points || by_rounds
+    }
+}
+
+/// Index of the player who won the ended game: the one left undefeated, or the one with the highest score.
+/// None if there is a tie.
+pub fn winner(boards: &[BoardState]) -> Option<usize> {
+    let standing: Vec<usize> = (0..boards.len())
+        .filter(|idx| !boards[*idx].is_defeated())
+        .collect();
+
+    let best = standing.iter().map(|idx| boards[*idx].score()).max()?;
+    let mut best_boards = standing.into_iter().filter(|idx| boards[*idx].score() == best);
+    match (best_boards.next(), best_boards.next()) {
+        (Some(idx), None) => Some(idx),
+        _ => None
+    }
+}
+
 #[derive(Debug, Default)]
 pub struct GameControlState {
     player_boards: Vec<BoardState>,
     current_player_idx: usize,
     round: i16,
+    victory: VictoryRules,
+    event_deck: Deck, // one event is drawn every round
+    explanations: ExplanationSettings,
+    pacing: AiPacing,
+    analyst: Option<Box<AI>>, // looks for mistakes when the game is over, if the game wants it to
+    decisions: Vec<RecordedDecision>, // made by the players in their turns, for the analyst
     assets: Assets
 }
 
 impl GameControlState {
-    pub fn new(player_boards: Vec<BoardState>, assets: Assets) -> Self {
+    pub fn new(player_boards: Vec<BoardState>, assets: Assets, victory: VictoryRules, event_deck: Deck, explanations: ExplanationSettings, pacing: AiPacing, analyst: Option<Box<AI>>) -> Self {
         Self { player_boards: player_boards,
             current_player_idx: 0,
             round: 0,
+            victory: victory,
+            event_deck: event_deck,
+            explanations: explanations,
+            pacing: pacing,
+            analyst: analyst,
+            decisions: Vec::new(),
             assets: assets }
     }
 
     pub fn overtake(&mut self) -> Box<dyn AutomatonState> {
+        self.begin_round();
         self.start_player_turn(0)
     }
 
+    /// Draws the event of the new round and hands it to all boards. The previous event goes to the bottom of the deck.
+    pub fn begin_round(&mut self) {
+        if let Some(previous) = self.player_boards.first().and_then(|b| b.event.clone()) {
+            self.event_deck.add(previous);
+        }
+
+        let event = self.event_deck.draw();
+        for board in self.player_boards.iter_mut() {
+            if let Some(event) = &event {
+                board.log_notice(Notice::Event{name: event.name.clone()});
+            }
+            board.event = event.clone();
+            board.apply_auras();
+        }
+    }
+
+    /// Passes the turn to the next player, beginning a new round if needed. Returns whether the game has ended.
+    pub fn next_turn(&mut self) -> bool {
+        self.current_player_idx += 1;
+
+        if self.current_player_idx >= self.player_boards.len() {
+            self.round += 1;
+            self.current_player_idx = 0;
+            self.begin_round();
+        }
+
+        self.player_boards.iter()
+            .filter(|b| !b.is_defeated())
+            .count() == 1 || self.victory.game_ended(&self.player_boards, self.round)
+    }
+
+    pub fn current_player(&self) -> usize {
+        self.current_player_idx
+    }
+
+    pub fn round(&self) -> i16 {
+        self.round
+    }
+
+    pub fn boards(&self) -> &[BoardState] {
+        &self.player_boards
+    }
+
     pub fn get_board(&self, idx: usize) -> &BoardState {
         &self.player_boards[idx]
     }
@@ -77,6 +177,27 @@ impl GameControlState {
         &self.assets
     }
 
+    pub fn explanations(&self) -> &ExplanationSettings {
+        &self.explanations
+    }
+
+    pub fn pacing(&self) -> &AiPacing {
+        &self.pacing
+    }
+
+    pub fn pacing_mut(&mut self) -> &mut AiPacing {
+        &mut self.pacing
+    }
+
+    /// Are decisions of the players kept for the analysis after the game?
+    pub fn analysing(&self) -> bool {
+        self.analyst.is_some()
+    }
+
+    pub fn record(&mut self, decision: RecordedDecision) {
+        self.decisions.push(decision);
+    }
+
     pub fn start_player_turn(&mut self, idx: usize) -> Box<dyn AutomatonState> {
         let opponent_idx = self.player_boards[idx].player.opponent_idx;
         GameplayState::new_with_ui(Box::new(take(self)), idx, opponent_idx)
@@ -90,22 +211,10 @@ impl AutomatonState for GameControlState {
 
         match event {
             GameEvent::EndTurn => {
-                let game_ended = self.player_boards.iter()
-                    .filter(|b| !b.is_defeated())
-                    .count() == 1;
-                
-                if game_ended {
+                if self.next_turn() {
                     let me = take(self);
-                    GameEndState::new(me.player_boards, me.assets)
+                    GameEndState::new(me.player_boards, me.assets, me.decisions, me.analyst)
                 } else {
-                    self.current_player_idx += 1;
-
-                    if self.current_player_idx >= self.player_boards.len() {
-                        self.round += 1;
-                        self.current_player_idx = 0;
-                        // handle end of round here, if it ever mattered
-                    }
-                    
                     //let board = &mut self.player_boards[self.current_player_idx];
                     self.start_player_turn(self.current_player_idx)
                 }
@@ -120,3 +229,61 @@ impl AutomatonState for GameControlState {
         Box::new(take(self))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game_objects::{Card, Aura, BoardZone, Modifier};
+    use crate::game_objects::fixtures::*;
+
+    fn scored(points: i16, buildings: usize) -> BoardState {
+        BoardState{ points, ..board(Vec::new(), (0..buildings).map(|_| named("house")).collect(), Vec::new()) }
+    }
+
+    #[test]
+    fn highest_score_wins() {
+        assert_eq!(winner(&[scored(3, 1), scored(5, 1)]), Some(1));
+    }
+
+    #[test]
+    fn tie_is_a_draw() {
+        assert_eq!(winner(&[scored(4, 1), scored(4, 2)]), None);
+    }
+
+    #[test]
+    fn defeated_player_loses_whatever_the_score() {
+        assert_eq!(winner(&[scored(9, 0), scored(1, 1)]), Some(1));
+    }
+
+    #[test]
+    fn nobody_wins_when_everyone_is_defeated() {
+        assert_eq!(winner(&[scored(2, 0), scored(1, 0)]), None);
+    }
+
+    #[test]
+    fn game_ends_at_the_points_or_rounds_set() {
+        let boards = [scored(4, 1), scored(7, 1)];
+
+        assert!(!VictoryRules::default().game_ended(&boards, 100));
+        assert!(VictoryRules{ points: Some(7), rounds: None }.game_ended(&boards, 1));
+        assert!(!VictoryRules{ points: Some(8), rounds: Some(10) }.game_ended(&boards, 9));
+        assert!(VictoryRules{ points: Some(8), rounds: Some(10) }.game_ended(&boards, 10));
+    }
+
+    #[test]
+    fn every_round_draws_an_event_for_all_boards() {
+        let calm = Card{ auras: vec!(Aura{ affects: BoardZone::Hand, tag: None, modifier: Modifier::Cost{val: -1} }), ..named("calm") };
+        let boards = vec!(scored(0, 1), board(vec!(named("card")), Vec::new(), Vec::new()));
+        let mut control = GameControlState::new(boards, Assets::default(), VictoryRules::default(), Deck::from(vec!(calm, named("storm"))),
+            Default::default(), Default::default(), None);
+
+        control.begin_round();
+        assert!(control.player_boards.iter().all(|b| b.event.as_ref().map(|e| e.name.as_str()) == Some("calm")));
+        assert_eq!(control.player_boards[1].hand.cards[0].modifiers.len(), 1);
+
+        control.begin_round();
+        assert!(control.player_boards.iter().all(|b| b.event.as_ref().map(|e| e.name.as_str()) == Some("storm")));
+        assert!(control.player_boards[1].hand.cards[0].modifiers.is_empty());
+        assert_eq!(names(control.event_deck.iter()), ["calm"]);
+    }
+}
@@ -5,15 +5,22 @@ use serde_derive::*;
 use quicksilver::prelude::*;
 
 use crate::automaton::*;
-use crate::ui::{TakeTurnState, TargetingState};
+use crate::ui::{TakeTurnState, AiTurnState, TargetingState, ReactionState, PromptState, ArrangeState, ChoiceState, ExtraCostState};
 use crate::game_objects::*;
 use crate::loading::Assets;
 use crate::game_control::{Player, PlayerControl, GameControlState};
-use crate::ai::AI;
+use crate::ai::{AI, AiPacing, Explanation};
+use crate::observation::Observation;
+use crate::analysis::RecordedDecision;
+use std::fs::OpenOptions;
+use std::io::Write;
 use std::mem::take;
+use std::rc::Rc;
+use derivative::*;
 use quicksilver::graphics::PixelFormat;
 
-#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[derive(Serialize, Deserialize, Derivative, Clone, Default)]
+#[derivative(Debug)]
 pub struct BoardState {
     pub player: Player,
     pub hand: Box<CardContainer>,
@@ -26,7 +33,32 @@ pub struct BoardState {
     pub buildings: Box<CardContainer>,
     // FIXME: make this a vector, or a type that can be iterated
     pub kaiju_zone: Box<CardContainer>,
-    pub ai: Option<Box<AI>>
+    pub kaiju_overflow: KaijuOverflow,
+    pub points: i16, // victory points from effects and defeated kaiju; see score()
+    pub event: Option<Card>, // global event of the current round: its auras and turn start effects apply to us
+    pub stack: Box<EffectStack>,
+    pub pending: Option<Decision>, // the player must decide this before the stack resolves further
+    pub log: Vec<Notice>,
+    pub peeked: Vec<Card>, // top of the opponent's deck as we last saw it, topmost first
+    pub ai: Option<Box<AI>>, // plays for AI players, gives hints to human ones
+    #[serde(skip)]
+    pub simulated: bool, // a copy the AI plays with: it keeps quiet
+    #[serde(skip)]
+    pub explained: Vec<String>, // AI decisions in its current turn, for the opponent to see
+    #[serde(skip)]
+    #[derivative(Debug = "ignore")]
+    pub card_factory: Rc<CardFactory>, // to create new cards during play
+}
+
+const LOG_LENGTH: usize = 5; // number of notices kept on the board
+
+/// Prints the line, unless the board is just a simulation
+macro_rules! say {
+    ($board:expr, $($arg:tt)*) => {
+        if !$board.simulated {
+            println!($($arg)*)
+        }
+    }
 }
 
 impl BoardState {
@@ -36,32 +68,125 @@ impl BoardState {
     }
     */
 
-     fn play_card(&mut self, card: usize) -> Card {
+    /// Takes the card from hand and puts it on the effect stack. Its effects are applied when the stack resolves.
+    /// Plays the card, paying its extra cost (if any) with the card at index `payment` in the cost's zone
+    fn play_card(&mut self, card: usize, target: Option<(BoardZone, usize)>, payment: Option<usize>) {
+        // the target is remembered by id: paying the extra cost or responses may move it
+        let target = target.and_then(|(zone, idx)| self.container_by_zone(zone).cards.get(idx)
+            .map(|target| (zone, target.id)));
+
         let played = self.hand.remove(card)
                         .expect(format!("WTF? Playing card not in hand? I should play card #{:?} when my gameplay state is: {:?}", card, self).as_str());
 
-        println!("Played card {}", played.name);
+        say!(self, "Played card {}", played.name);
+
+        // the index was chosen while the card was still in hand
+        let payment = match (played.extra_cost, payment) {
+            (Some(ExtraCost::Discard), Some(idx)) if idx > card => Some(idx - 1),
+            _ => payment
+        };
+
+        let mut item = StackItem::new(Trigger::Play, BoardZone::Hand, played.clone(), played.on_play.clone());
+        item.target = target;
+        self.stack.push(item);
 
-        for effect in &played.on_play {
-            self.evaluate_effect(effect, played.clone())
+        // whatever the payment triggers goes on top of the card, resolving first
+        if let (Some(extra_cost), Some(idx)) = (played.extra_cost, payment) {
+            self.pay_extra_cost(extra_cost, idx);
         }
+    }
 
-        played
+    fn pay_extra_cost(&mut self, extra_cost: ExtraCost, idx: usize) {
+        match extra_cost {
+            ExtraCost::Sacrifice => self.destroy_building(idx),
+            ExtraCost::Discard => {
+                if let Some(discarded) = self.hand.remove(idx) {
+                    self.trigger(Trigger::Discarded, BoardZone::Hand, &discarded);
+                }
+            },
+            ExtraCost::ReturnKaiju => {
+                if let Some(kaiju) = self.kaiju_zone.remove(idx) {
+                    self.deck.add(kaiju);
+                }
+            },
+        }
     }
 
-    fn play_card_on_target(&mut self, card_idx: usize, target_zone: BoardZone, target_idx: usize) {
-        let played = self.play_card(card_idx);
-        let target_container = self.container_by_zone(target_zone);
+    /// Indices of cards (in the extra cost's zone) the card in hand can be played with. The card itself
+    /// and its target can't be used.
+    pub fn extra_cost_options(&self, card_idx: usize, target: Option<(BoardZone, usize)>) -> Vec<usize> {
+        let extra_cost = match self.hand.cards[card_idx].extra_cost {
+            Some(extra_cost) => extra_cost,
+            None => return Vec::new()
+        };
+
+        let zone = extra_cost.zone();
+        let count = match zone {
+            BoardZone::Buildings => self.buildings.cards.len(),
+            BoardZone::Hand => self.hand.cards.len(),
+            BoardZone::Kaiju => self.kaiju_zone.cards.len(),
+            _ => 0
+        };
 
-        println!("   on target {}", target_idx);
+        (0..count)
+            .filter(|idx| !(zone == BoardZone::Hand && *idx == card_idx))
+            .filter(|idx| target != Some((zone, *idx)))
+            .collect()
+    }
+
+    /// Can the extra cost of the card in hand be paid (or does it have none) when it's played on the target?
+    pub fn can_pay_extra(&self, card_idx: usize, target: Option<(BoardZone, usize)>) -> bool {
+        self.hand.cards[card_idx].extra_cost.is_none() || !self.extra_cost_options(card_idx, target).is_empty()
+    }
+
+    /// Indices of cards the card in hand can be played on, its extra cost paid
+    pub fn payable_targets(&self, card_idx: usize) -> Vec<usize> {
+        let zone = self.hand.cards[card_idx].target_zone;
+        let count = match zone {
+            BoardZone::Kaiju => self.kaiju_zone.cards.len(),
+            _ => 0 // nothing targets other zones yet
+        };
+
+        (0..count)
+            .filter(|target| self.can_pay_extra(card_idx, Some((zone, *target))))
+            .collect()
+    }
+
+    /// Can the card in hand be played now, on some target if it needs one?
+    pub fn can_play(&self, card_idx: usize) -> bool {
+        match self.hand.cards[card_idx].target_zone {
+            BoardZone::None => self.can_pay_extra(card_idx, None),
+            _ => !self.payable_targets(card_idx).is_empty()
+        }
+    }
+
+    fn apply_target_effect(&mut self, played: &Card, target_zone: BoardZone, target_id: usize) {
+        // the target might have left while the card waited on the stack
+        let target_idx = match self.container_by_zone(target_zone).cards.iter().position(|c| c.id == target_id) {
+            Some(idx) => idx,
+            None => {
+                say!(self, "  The target of {} is gone, it fizzles", played.name);
+                return;
+            }
+        };
+
+        say!(self, "   on target {}", target_idx);
+        let target_container = self.container_by_zone(target_zone);
 
         match played.target_effect {
-            TargetEffect::None => { print!("*Sad trombone*"); },
-            TargetEffect::Stun => { target_container.get(card_idx).stunned = true; }
-            TargetEffect::Kill => { target_container.remove(target_idx); },
+            TargetEffect::None => { say!(self, "*Sad trombone*"); },
+            TargetEffect::Stun => { target_container.cards[target_idx].stunned = true; }
+            TargetEffect::Kill => {
+                if let Some(target) = target_container.remove(target_idx) {
+                    if target_zone == BoardZone::Kaiju {
+                        self.points += target.points;
+                    }
+                    self.trigger(Trigger::Destroyed, target_zone, &target);
+                }
+            },
             TargetEffect::Bounce => {
                 if let Some(target) = target_container.remove(target_idx) {
-                    self.deck.add(target);
+                    self.place(target, played.bounce_to);
                 }
             },
         }
@@ -75,47 +200,172 @@ impl BoardState {
         match self.deck.draw() {
             None => false,
             Some(card) => {
-                self.container_by_zone(card.draw_to).add(card);
+                match card.draw_to {
+                    BoardZone::Kaiju => self.add_kaiju(card),
+                    BoardZone::Hand => {
+                        self.trigger(Trigger::Drawn, BoardZone::Hand, &card);
+                        self.hand.add(card);
+                    },
+                    zone => self.container_by_zone(zone).add(card)
+                }
                 
                 true},
         }
     }
 
+    /// Puts the card where placement says. Cards that don't fit to hand go to the bottom of the deck.
+    pub fn place(&mut self, card: Card, placement: Placement) {
+        match placement {
+            Placement::Hand if !self.hand.is_full() => self.hand.add(card),
+            Placement::Hand | Placement::Bottom => self.deck.add(card),
+            Placement::Kaiju => self.add_kaiju(card),
+            Placement::Top => self.deck.add_top(card),
+            Placement::Shuffle => self.deck.shuffle_in(card),
+        }
+    }
+
+    /// Puts the kaiju to the kaiju zone, triggering arrival effects of the kaiju itself and all other cards in play.
+    /// When the zone is full, overflow rule decides what happens.
+    pub fn add_kaiju(&mut self, kaiju: Card) {
+        if self.kaiju_zone.is_full() {
+            self.log_notice(Notice::NoRoom{kaiju: kaiju.name.clone()});
+
+            match self.kaiju_overflow {
+                KaijuOverflow::Bottom => {
+                    self.deck.add(kaiju);
+                    return;
+                },
+                KaijuOverflow::Strike => {
+                    let strike = StackItem::new(Trigger::Strike, BoardZone::Kaiju, kaiju.clone(), kaiju.strike_effects());
+                    self.deck.add(kaiju);
+                    self.stack.push(strike);
+                    return;
+                },
+                KaijuOverflow::ReplaceWeakest => {
+                    let weakest = self.kaiju_zone.cards.iter()
+                        .enumerate()
+                        .min_by_key(|(_, k)| k.threat())
+                        .map(|(idx, _)| idx);
+                    if let Some(replaced) = weakest.and_then(|idx| self.kaiju_zone.remove(idx)) {
+                        self.deck.add(replaced);
+                    }
+                }
+            }
+        }
+
+        let mut arrivals = vec!();
+        if !kaiju.on_kaiju_arrives.is_empty() {
+            arrivals.push(StackItem::new(Trigger::KaijuArrives, BoardZone::Kaiju, kaiju.clone(), kaiju.on_kaiju_arrives.clone()));
+        }
+        arrivals.extend(self.buildings.triggered(Trigger::KaijuArrives));
+        arrivals.extend(self.kaiju_zone.triggered(Trigger::KaijuArrives));
+
+        self.kaiju_zone.add(kaiju);
+        self.stack.push_ordered(arrivals);
+    }
+
+    /// Removes the building from play, triggering its on_destroyed effects
+    fn destroy_building(&mut self, idx: usize) {
+        if let Some(building) = self.buildings.remove(idx) {
+            self.trigger(Trigger::Destroyed, BoardZone::Buildings, &building);
+        }
+    }
+
+    /// Uses the activated ability of the building: pays its cost and puts its effects on the stack
+    pub fn activate(&mut self, idx: usize) {
+        let building = &mut self.buildings.cards[idx];
+        building.exhausted = true;
+        let building = building.clone();
+
+        if let Some(ability) = &building.activated {
+            self.globals.pay(&ability.cost);
+        }
+        self.trigger(Trigger::Activate, BoardZone::Buildings, &building);
+    }
+
+    /// Puts the card's effects linked to the trigger on the stack (if it has any)
+    pub fn trigger(&mut self, trigger: Trigger, zone: BoardZone, card: &Card) {
+        let effects = card.effects_on(trigger);
+        if !effects.is_empty() {
+            self.stack.push(StackItem::new(trigger, zone, card.clone(), effects.clone()));
+        }
+    }
+
+    /// Puts turn start effects on the stack and draws a new hand. The stack is resolved by the caller.
     pub fn begin_turn(&mut self) {
-        println!("Starting turn {}", self.turn);
+        say!(self, "Starting turn {}", self.turn);
+        self.explained.clear();
 
-        // process on_begin
-        for (_, card, effect) in self.buildings.all_effects(|c| &c.on_turn_start) {
-            self.evaluate_effect(&effect, card)
+        let mut countdowns = vec!();
+        for container in vec!(self.buildings.as_mut(), self.kaiju_zone.as_mut()) {
+            for card in container.cards.iter_mut() {
+                card.turns_in_play += 1;
+                if card.countdown.is_some_and(|countdown| card.turns_in_play >= countdown) {
+                    countdowns.push(StackItem::new(Trigger::Countdown, container.zone, card.clone(), card.on_countdown.clone()));
+                }
+            }
+        }
+        self.stack.push_ordered(countdowns);
+
+        // kaiju that survived since our last turn grow
+        let mut grown = vec!();
+        for kaiju in self.kaiju_zone.cards.iter_mut().filter(|k| k.turns_in_play >= 1) {
+            if let Some(escalation) = &kaiju.escalation {
+                if !escalation.grow.is_empty() {
+                    kaiju.grown += 1;
+                    grown.push(Notice::Grows{kaiju: kaiju.name.clone()});
+                }
+            }
         }
+        for notice in grown {
+            self.log_notice(notice);
+        }
+
+        // process on_begin
+        let mut triggered = match &self.event {
+            Some(event) if !event.on_turn_start.is_empty() =>
+                vec!(StackItem::new(Trigger::TurnStart, BoardZone::None, event.clone(), event.on_turn_start.clone())),
+            _ => vec!()
+        };
+        triggered.extend(self.buildings.triggered(Trigger::TurnStart));
+        triggered.extend(self.kaiju_zone.triggered(Trigger::TurnStart));
+        self.stack.push_ordered(triggered);
 
-        // draw full hand
-        while !self.hand.is_full() {
-            if !self.draw_card() {
+        // draw full hand; kaiju that don't fit go back to the deck, so no card is drawn twice
+        for _ in 0..self.deck.len() {
+            if self.hand.is_full() || !self.draw_card() {
                 break;
             }
         }
 
         //for zone in [self.hand, self.kaiju_zone, self.buildings, self.store_fixed, self.store_trade]:
         // 
-        for card in self.kaiju_zone.cards.iter_mut() {
-            card.reset()
-    }
+        for container in vec!(self.kaiju_zone.as_mut(), self.buildings.as_mut()) {
+            for card in container.cards.iter_mut() {
+                card.reset()
+            }
+        }
+
+        self.apply_auras();
     }
 
+    /// Puts kaiju strikes and turn end effects on the stack. When the stack is resolved, call finish_turn.
     pub fn end_turn(&mut self) {
-        println!("Ending turn {}", self.turn);
-        println!();
+        say!(self, "Ending turn {}", self.turn);
 
-        for (_, card, effect) in self.kaiju_zone.all_effects(|c| &c.on_strike) {
-            if !card.stunned {
-                self.evaluate_effect(&effect, card)
-            }
-        }
+        self.apply_auras();
 
-        for (_, card, effect) in self.buildings.all_effects(|c| &c.on_turn_end) {
-            self.evaluate_effect(&effect, card)
-        }
+        let mut triggered: Vec<StackItem> = self.kaiju_zone.cards.iter()
+            .filter(|kaiju| !kaiju.stunned)
+            .map(|kaiju| StackItem::new(Trigger::Strike, BoardZone::Kaiju, kaiju.clone(), kaiju.strike_effects()))
+            .filter(|item| !item.effects.is_empty())
+            .collect();
+        triggered.extend(self.buildings.triggered(Trigger::TurnEnd));
+        self.stack.push_ordered(triggered);
+    }
+
+    pub fn finish_turn(&mut self) {
+        say!(self, "");
 
         self.globals.reset_all();
 
@@ -123,26 +373,462 @@ impl BoardState {
         self.turn += 1;
     }
 
+    /// Gives cards in play a chance to respond to the item before it resolves.
+    /// Returns responding items, which should resolve before the item itself.
+    pub fn response_window(&mut self, item: &mut StackItem) -> Vec<StackItem> {
+        item.responded = true;
+        let mut responding = Vec::new();
+
+        for container in vec!(self.kaiju_zone.as_mut(), self.buildings.as_mut()) {
+            let zone = container.zone;
+
+            for card in container.cards.iter_mut().filter(|c| !c.stunned) {
+                for response_idx in 0..card.responses.len() {
+                    let response = card.responses[response_idx].clone();
+                    if !response.matches(item) || !card.use_response(response_idx) {
+                        continue;
+                    }
+
+                    say!(self, "  {} responds to {}", card.name, item.source.name);
+                    if response.counter {
+                        item.countered = true;
+                    }
+
+                    responding.push(StackItem::answering(item.trigger, zone, card.clone(), response.effects));
+                }
+            }
+        }
+
+        responding
+    }
+
+    /// Opens the response window for an item that did not have it yet. If anything responds, the item is put
+    /// back on the stack beneath the responses and None is returned; otherwise the item is ready to resolve.
+    pub fn take_responses(&mut self, mut item: StackItem) -> Option<StackItem> {
+        if item.responded {
+            return Some(item);
+        }
+
+        let responses = self.response_window(&mut item);
+        if responses.is_empty() {
+            Some(item)
+        } else {
+            self.stack.push(item);
+            self.stack.push_ordered(responses);
+            None
+        }
+    }
+
+    /// Resolves the whole stack without handing control to anyone: only cards in play get to respond.
+    /// Used for boards whose player is not on turn.
+    pub fn resolve_stack(&mut self) {
+        while let Some(item) = self.stack.pop() {
+            if let Some(mut item) = self.take_responses(item) {
+                self.intercept_automatically(&mut item);
+                self.resolve_item(item);
+            }
+
+            if let Some(decision) = self.pending.take() {
+                self.decide_automatically(decision);
+            }
+        }
+    }
+
+    /// Makes a decision without asking the player: our AI (or a default one for human players) decides.
+    /// Only our own deck can be rearranged this way; the opponent's one is out of reach.
+    fn decide_automatically(&mut self, decision: Decision) {
+        match decision {
+            Decision::Arrange{whose: Whose::Own, n, rearrange: true} => {
+                let cards = self.deck.peek(n);
+                let observation = Observation::of_board(self);
+                let (top, bottom) = match &self.ai {
+                    Some(ai) => ai.strategy().arrange(&observation, &cards, true),
+                    None => AI::default().strategy().arrange(&observation, &cards, true)
+                };
+                self.deck.arrange_top(n, &top, &bottom);
+            },
+            Decision::Arrange{..} => say!(self, "  Nobody is looking"),
+            Decision::Choose{options, picks} => {
+                let observation = Observation::of_board(self);
+                let chosen = match &self.ai {
+                    Some(ai) => ai.strategy().choose(&observation, &options, picks),
+                    None => AI::default().strategy().choose(&observation, &options, picks)
+                };
+                self.continue_with(Mode::chosen_effects(&options, &chosen));
+            }
+        }
+    }
+
+    /// Puts the effects in front of the rest of the item that waited for a decision, so they resolve first
+    pub fn continue_with(&mut self, effects: Vec<Effect>) {
+        if let Some(mut rest) = self.stack.pop() {
+            rest.effects.splice(0..0, effects);
+            self.stack.push(rest);
+        }
+    }
+
+    /// Index of the next kaiju that could intercept the item (a card being played), if there is any.
+    /// Kaiju that let the item through already are not asked again.
+    pub fn interceptor_for(&self, item: &StackItem) -> Option<usize> {
+        if item.trigger != Trigger::Play || item.answer || item.countered {
+            return None;
+        }
+
+        self.kaiju_zone.cards.iter().position(|k| k.can_intercept(&item.source) && !item.declined.contains(&k.id))
+    }
+
+    /// Lets a kaiju intercept the item before it resolves, without asking anyone: kaiju sent by the opponent
+    /// decide as the default AI would (the opponent's own AI is out of reach), the rest intercept whenever they can
+    fn intercept_automatically(&mut self, item: &mut StackItem) {
+        if item.intercept_checked {
+            return;
+        }
+
+        while let Some(kaiju_idx) = self.interceptor_for(item) {
+            let kaiju = &self.kaiju_zone.cards[kaiju_idx];
+            let intercepting = match kaiju.owner {
+                None => true,
+                Some(_) => AI::default().strategy().intercept(&Observation::of_board(self), kaiju, item)
+            };
+            self.intercept(kaiju_idx, item, intercepting);
+        }
+        item.intercept_checked = true;
+    }
+
+    /// Carry out (or decline) interception of the item by the kaiju. Returns the notice of it, for the other
+    /// player to hear too.
+    pub fn intercept(&mut self, kaiju_idx: usize, item: &mut StackItem, intercepting: bool) -> Notice {
+        let kaiju = &mut self.kaiju_zone.cards[kaiju_idx];
+        let notice = if intercepting {
+            kaiju.intercepts_left -= 1;
+            item.countered = true;
+            Notice::Intercepted{kaiju: kaiju.name.clone(), card: item.source.name.clone()}
+        } else {
+            item.declined.push(kaiju.id);
+            Notice::InterceptDeclined{kaiju: kaiju.name.clone(), card: item.source.name.clone()}
+        };
+
+        say!(self, "  {}", notice);
+        self.log_notice(notice.clone());
+        notice
+    }
+
+    pub fn log_notice(&mut self, notice: Notice) {
+        self.log.push(notice);
+        if self.log.len() > LOG_LENGTH {
+            self.log.remove(0);
+        }
+    }
+
+    /// Indices of cards in hand that can be played as a reaction to the item
+    pub fn reactions_to(&self, item: &StackItem) -> Vec<usize> {
+        self.hand.cards.iter()
+            .enumerate()
+            .filter(|(_, c)| match &c.reaction {
+                Some(reaction) => reaction.matches(item),
+                None => false
+            })
+            .map(|(i, _)| i)
+            .collect()
+    }
+
+    pub fn resolve_item(&mut self, item: StackItem) {
+        if item.countered {
+            say!(self, "  {} was countered", item.source.name);
+            if item.trigger == Trigger::Play && !item.answer {
+                self.trigger(Trigger::Discarded, BoardZone::Hand, &item.source);
+            }
+            return;
+        }
+
+        for (num, effect) in item.effects.iter().enumerate() {
+            if let Some(decision) = effect.decision() {
+                // the rest of the item waits on the stack until the decision is made
+                let mut rest = item.clone();
+                rest.effects = item.effects[num + 1..].to_vec();
+                rest.responded = true;
+                rest.intercept_checked = true;
+                self.stack.push(rest);
+                self.pending = Some(decision);
+                return;
+            }
+
+            self.evaluate_effect(effect, item.source.clone())
+        }
+
+        if let Some((target_zone, target_id)) = item.target {
+            self.apply_target_effect(&item.source, target_zone, target_id);
+        }
+
+        if item.trigger == Trigger::Strike && !item.answer {
+            self.after_strike(item.source.id);
+        }
+
+        self.apply_auras();
+    }
+
+    /// Counts the strike to the kaiju, and applies its escalation rules
+    fn after_strike(&mut self, kaiju_id: usize) {
+        let kaiju_idx = match self.kaiju_zone.cards.iter().position(|k| k.id == kaiju_id) {
+            Some(idx) => idx,
+            None => return // kaiju left already
+        };
+
+        let kaiju = &mut self.kaiju_zone.cards[kaiju_idx];
+        kaiju.strikes += 1;
+
+        let escalation = match &kaiju.escalation {
+            Some(escalation) => escalation.clone(),
+            None => return
+        };
+        let kaiju = kaiju.clone();
+
+        if escalation.rampage_at == Some(kaiju.strikes) {
+            self.log_notice(Notice::Rampage{kaiju: kaiju.name.clone()});
+            self.trigger(Trigger::Rampage, BoardZone::Kaiju, &kaiju);
+        }
+
+        if escalation.leaves_after.is_some_and(|leaves_after| kaiju.strikes >= leaves_after) {
+            self.log_notice(Notice::KaijuLeft{kaiju: kaiju.name.clone()});
+            self.kaiju_zone.remove(kaiju_idx);
+        }
+    }
+
+    /// Recomputes modifiers of all cards from auras of the cards in play
+    pub fn apply_auras(&mut self) {
+        let auras: Vec<Aura> = self.buildings.cards.iter()
+            .chain(self.kaiju_zone.cards.iter())
+            .chain(self.event.iter())
+            .flat_map(|c| c.auras.iter().cloned())
+            .collect();
+
+        let mut containers = vec!(self.hand.as_mut(), self.buildings.as_mut(), self.kaiju_zone.as_mut());
+        containers.extend(self.stores.iter_mut().map(|s| &mut s.menu));
+
+        for container in containers {
+            let zone = container.zone;
+            for card in container.cards.iter_mut() {
+                card.modifiers = auras.iter()
+                    .filter(|a| a.affects_card(zone, card))
+                    .map(|a| a.modifier.clone())
+                    .collect();
+            }
+        }
+    }
+
     pub fn evaluate_effect(&mut self, effect: &Effect, card: Card) {
         match effect {
             Effect::Break => {
                 let cost = Cost{currency: Globals::Block, count: 1};
                 if self.globals.can_afford(&cost) {
-                    self.globals.pay(&cost)
+                    self.globals.pay(&cost);
+                    if let Some(defended) = self.buildings.cards.first().cloned() {
+                        self.trigger(Trigger::Defend, BoardZone::Buildings, &defended);
+                    }
                 } else if self.buildings.cards.len() > 0 {
-                    self.buildings.remove(0);
+                    self.destroy_building(0);
                 }},
             Effect::BreakEverything => {
                 while !self.buildings.empty() {
-                    self.buildings.remove(0);
+                    self.destroy_building(0);
                 }},
-            Effect::BreakUnblockable => {self.buildings.remove(0);},
-            Effect::Echo{msg} => println!("  {}", msg),
+            Effect::BreakUnblockable => {self.destroy_building(0);},
+            Effect::Echo{msg} => say!(self, "  {}", msg),
             Effect::Global{key, val} => self.globals.add(*key, *val),
-            Effect::None => println!("  It does nothing"),
-            Effect::Return => { self.deck.add(card) },
+            Effect::Points{val} => self.points += val,
+            Effect::None => say!(self, "  It does nothing"),
+            Effect::Return{to} => self.place(card, *to),
+            Effect::Spawn{card: name, zone, count} => {
+                for _ in 0..*count {
+                    if let Some(mut spawned) = self.new_card(name) {
+                        say!(self, "  {} spawns {}", card.name, spawned.name);
+                        spawned.owner = card.owner;
+                        match zone {
+                            BoardZone::Kaiju => self.add_kaiju(spawned),
+                            BoardZone::Hand => self.place(spawned, Placement::Hand),
+                            BoardZone::Buildings => self.buildings.add(spawned),
+                            _ => say!(self, "  Can't spawn cards to {:?}", zone)
+                        }
+                    }
+                }
+            },
+            Effect::Transform{into} => self.transform(&card, into),
             Effect::ToBuildings => { self.buildings.add(card) },
+            Effect::Peek{..} | Effect::Scry{..} | Effect::Choose{..} => {}, // player decides; see resolve_item
+        }
+    }
+
+    /// Creates a new card instance from the card factory
+    pub fn new_card(&self, name: &str) -> Option<Card> {
+        match self.card_factory.get(name) {
+            Some(template) => Some(template.instantiate()),
+            None => {
+                say!(self, "  Unknown card {}", name);
+                None
+            }
+        }
+    }
+
+    /// Zone and index of the card instance, if it is in hand or in play
+    pub fn find_card(&self, card_id: usize) -> Option<(BoardZone, usize)> {
+        vec!(self.hand.as_ref(), self.buildings.as_ref(), self.kaiju_zone.as_ref()).into_iter()
+            .find_map(|container| container.cards.iter()
+                .position(|c| c.id == card_id)
+                .map(|idx| (container.zone, idx)))
+    }
+
+    /// Replaces the card with a new one, in the same place
+    fn transform(&mut self, card: &Card, into: &str) {
+        let (zone, idx) = match self.find_card(card.id) {
+            Some(location) => location,
+            None => {
+                say!(self, "  {} is gone, it can't transform", card.name);
+                return;
+            }
+        };
+        let mut new_card = match self.new_card(into) {
+            Some(new_card) => new_card,
+            None => return
+        };
+
+        say!(self, "  {} transforms into {}", card.name, new_card.name);
+        new_card.owner = card.owner;
+        new_card.reset();
+        self.container_by_zone(zone).cards[idx] = new_card.clone();
+
+        if zone == BoardZone::Kaiju {
+            self.trigger(Trigger::KaijuArrives, zone, &new_card);
+        }
+    }
+
+    pub fn can_buy(&self, zone: BoardZone, card_idx: usize) -> bool {
+        match self.stores.iter().find(|s| s.menu.zone == zone) {
+            Some(store) => card_idx < store.menu.cards.len()
+                && self.globals.can_afford(&store.menu.cards[card_idx].effective_cost()),
+            None => false
+        }
+    }
+
+    /// Buys the card from the store, paying its cost. The card is placed on our board, unless it goes to
+    /// the opponent: then it is returned.
+    pub fn buy_card(&mut self, zone: BoardZone, card_idx: usize) -> Option<Card> {
+        let card = self.store_by_zone(zone).buy_card(card_idx);
+
+        self.globals.pay(&card.effective_cost());
+        self.trigger(Trigger::Bought, zone, &card);
+
+        if card.give_to_enemy {
+            Some(card)
+        } else {
+            self.place(card.clone(), card.placement);
+            None
+        }
+    }
+
+    /// Takes a card the opponent bought for us
+    pub fn receive(&mut self, card: Card) {
+        let placement = card.placement;
+        self.place(card, placement);
+        self.resolve_stack(); // kaiju placed directly may trigger arrivals
+    }
+
+    /// A copy of the board to play with: it can be advanced by apply_action and start_turn without any UI
+    pub fn simulation(&self) -> BoardState {
+        let mut sim = self.clone();
+        sim.simulated = true;
+        sim
+    }
+
+    /// Begins our next turn without asking anyone, like apply_action does. The opponent's turn is not played.
+    pub fn start_turn(&mut self) {
+        self.begin_turn();
+        self.resolve_stack();
+        self.update_availability();
+    }
+
+    /// Everything our player can do now, ending the turn last. Cards that need a target are listed once
+    /// for every possible target.
+    pub fn legal_actions(&self) -> Vec<GameEvent> {
+        let mut actions = Vec::new();
+
+        for (idx, card) in self.hand.cards.iter().enumerate() {
+            match card.target_zone {
+                BoardZone::None => if self.can_pay_extra(idx, None) {
+                    actions.push(GameEvent::CardPicked(idx));
+                },
+                zone => actions.extend(self.payable_targets(idx).into_iter()
+                    .map(|target| GameEvent::CardTargeted(BoardZone::Hand, idx, zone, target))),
+            }
+        }
+
+        for store in self.stores.iter() {
+            actions.extend((0..store.menu.cards.len())
+                .filter(|idx| self.can_buy(store.menu.zone, *idx))
+                .map(|idx| GameEvent::CardBought(store.menu.zone, idx)));
         }
+
+        actions.extend((0..self.buildings.cards.len())
+            .filter(|idx| self.buildings.cards[*idx].can_activate(&self.globals))
+            .map(GameEvent::CardActivated));
+
+        actions.push(GameEvent::EndTurn);
+        actions
+    }
+
+    /// Carries out an action of our player without asking anyone, resolving the stack automatically. Targets
+    /// not given in the action and extra costs are picked by the default AI. Used by AI to simulate turns.
+    /// Returns the card bought for the opponent, if any.
+    pub fn apply_action(&mut self, action: &GameEvent) -> Option<Card> {
+        self.update_availability();
+
+        let given = match action {
+            GameEvent::CardPicked(idx) => {
+                let zone = self.hand.cards[*idx].target_zone;
+                match zone {
+                    BoardZone::None => self.play_automatically(*idx, None),
+                    _ => if let GameEvent::CardTargeted(_, _, zone, target) = AI::default().strategy().target_card(&Observation::of_board(self), *idx, zone) {
+                        if zone != BoardZone::None {
+                            self.play_automatically(*idx, Some((zone, target)));
+                        }
+                    }
+                }
+                None
+            },
+            GameEvent::CardTargeted(_, idx, zone, target) => {
+                self.play_automatically(*idx, Some((*zone, *target)));
+                None
+            },
+            GameEvent::CardBought(zone, idx) if self.can_buy(*zone, *idx) => self.buy_card(*zone, *idx),
+            GameEvent::CardActivated(idx) if self.buildings.cards[*idx].can_activate(&self.globals) => {
+                self.activate(*idx);
+                None
+            },
+            GameEvent::EndTurn => {
+                self.end_turn();
+                self.resolve_stack();
+                self.finish_turn();
+                None
+            },
+            _ => None
+        };
+
+        self.resolve_stack();
+        given
+    }
+
+    fn play_automatically(&mut self, idx: usize, target: Option<(BoardZone, usize)>) {
+        let payment = match self.hand.cards[idx].extra_cost {
+            Some(extra_cost) => {
+                let options = self.extra_cost_options(idx, target);
+                if options.is_empty() {
+                    return;
+                }
+                Some(AI::default().strategy().pay_extra_cost(&Observation::of_board(self), extra_cost, &options))
+            },
+            None => None
+        };
+
+        self.play_card(idx, target, payment);
     }
 
     pub fn store_by_zone(&mut self, zone: BoardZone) -> &mut Store {
@@ -152,9 +838,11 @@ impl BoardState {
     }
 
     pub fn update_availability(&mut self) {
+        self.apply_auras();
+
         for store in self.stores.iter_mut() {
             for card in store.menu.cards.iter_mut() {
-                card.available = self.globals.can_afford(&card.cost);
+                card.available = self.globals.can_afford(&card.effective_cost());
             }
         }
 
@@ -163,6 +851,14 @@ impl BoardState {
                 card.available = true;
             }
         }
+
+        for idx in 0..self.hand.cards.len() {
+            self.hand.cards[idx].available = self.can_play(idx);
+        }
+
+        for building in self.buildings.cards.iter_mut() {
+            building.available = building.activated.is_none() || building.can_activate(&self.globals);
+        }
     }
 
     pub fn container_by_zone(&mut self, zone: BoardZone) -> &mut CardContainer {
@@ -176,24 +872,46 @@ impl BoardState {
         }
     }
 
+    /// Victory points: those gained during the game, and those of our cards. Kaiju sent to us do not count.
+    pub fn score(&self) -> i16 {
+        let cards: i16 = self.deck.iter()
+            .chain(self.hand.cards.iter())
+            .chain(self.buildings.cards.iter())
+            .filter(|c| c.draw_to != BoardZone::Kaiju)
+            .map(|c| c.points)
+            .sum();
+
+        self.points + cards
+    }
+
     pub fn is_defeated(&self) -> bool {
         self.buildings.empty()
     }
 }
 
+/// Where we are in the turn: tells us what to do once the effect stack is empty.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Default)]
+enum TurnPhase {
+    #[default]
+    Starting,
+    Main,
+    Ending
+}
+
 #[derive(Debug, Default)]
 pub struct GameplayState {
     controller: Box<GameControlState>,
     // board: BoardState,
     // opponent_board: BoardState
     board_idx: usize,
-    opo_idx: usize
+    opo_idx: usize,
+    phase: TurnPhase
 }
 
 impl GameplayState {
     pub fn new(controller: Box<GameControlState>, board_idx: usize, opo_idx: usize) -> Box<Self> { //board: BoardState, opponent_board: BoardState) -> Box<Self> {
         //Box::new(Self{controller, board, opponent_board })
-        Box::new(Self{controller, board_idx, opo_idx })
+        Box::new(Self{controller, board_idx, opo_idx, phase: TurnPhase::Starting })
     }
 
     pub fn new_with_ui(controller: Box<GameControlState>, board_idx: usize, opo_idx: usize) -> Box<dyn AutomatonState> {
@@ -226,6 +944,20 @@ impl GameplayState {
         self.controller.get_assets()
     }
 
+    pub fn pacing_mut(&mut self) -> &mut AiPacing {
+        self.controller.pacing_mut()
+    }
+
+    /// What the player with the board of this index knows
+    pub fn observation(&self, board_idx: usize) -> Observation {
+        Observation::new(self.controller.boards(), board_idx)
+    }
+
+    /// Is the board with this index the one of the player on turn?
+    pub fn is_on_turn(&self, board_idx: usize) -> bool {
+        self.board_idx == board_idx
+    }
+
     // Performs all operations needed before switching control
     // either to player by going to TakeTurnState,
     // or AI by calling self.event with event obtained from AI object
@@ -235,61 +967,338 @@ impl GameplayState {
         match self.get_board().player.control {
             PlayerControl::Human => TakeTurnState::new(Box::new(take(self))),
             PlayerControl::AI => {
-                let board = self.get_board();
-                let ai = board.ai.as_ref().expect("AI for AI player not loaded");
-                let intent = ai.select_card(board);
-                self.event(intent)
+                let ai = self.get_board().ai.as_ref().expect("AI for AI player not loaded");
+                let explanation = ai.strategy().explain(&self.observation(self.board_idx));
+                self.explained(&explanation);
+                self.ai_acts(explanation.action)
             } 
         }
     }
-}
 
-impl AutomatonState for GameplayState {
-    fn event(&mut self, event: GameEvent) -> Box<dyn AutomatonState> {
-        println!("GameplayState received event: {:?}", event);
+    /// What the AI of the player on turn would do in their place, if they have one
+    pub fn hint(&self) -> Option<Explanation> {
+        let ai = self.get_board().ai.as_ref()?;
+        Some(ai.strategy().explain(&self.observation(self.board_idx)))
+    }
 
-        match event {
-            GameEvent::StartTurn => {
-                self.get_board_mut().begin_turn();
-                self.take_turn()
+    /// Keeps the decision of the player on turn (with the boards as they were) for the analysis after the game,
+    /// if there is one. Cards with targets are recorded when the target is picked.
+    fn record(&mut self, event: &GameEvent) {
+        if !self.controller.analysing() {
+            return;
+        }
+
+        let decided = match event {
+            GameEvent::CardPicked(idx) => self.get_board().hand.cards.get(*idx).is_some_and(|c| c.target_zone == BoardZone::None),
+            GameEvent::CardTargeted(_, _, target_zone, _) => *target_zone != BoardZone::None,
+            GameEvent::CardBought(..) | GameEvent::CardActivated(_) | GameEvent::EndTurn => true,
+            _ => false
+        };
+
+        if decided {
+            let decision = RecordedDecision {
+                player: self.board_idx,
+                round: self.controller.round(),
+                boards: self.controller.boards().to_vec(),
+                action: event.clone(),
+            };
+            self.controller.record(decision);
+        }
+    }
+
+    /// Does what the AI on turn decided: shows it for a while first, unless the AI plays instantly
+    fn ai_acts(&mut self, action: GameEvent) -> Box<dyn AutomatonState> {
+        if self.controller.pacing().millis == 0 {
+            self.event(action)
+        } else {
+            AiTurnState::new(Box::new(take(self)), action)
+        }
+    }
+
+    /// Logs the AI decision, to the file if the game says so, and keeps it for the panel if it's shown.
+    /// Simulated boards keep it only for the panel.
+    fn explained(&mut self, explanation: &Explanation) {
+        let board = self.get_board();
+        let text = format!("{} (turn {}) decided to {}", board.player.name, board.turn, explanation.describe(board));
+        say!(board, "{}", text);
+
+        let settings = self.controller.explanations().clone();
+        if let Some(path) = settings.log_file.as_ref().filter(|_| !board.simulated) {
+            let written = OpenOptions::new().create(true).append(true).open(path)
+                .and_then(|mut file| writeln!(file, "{}", text));
+            if let Err(e) = written {
+                println!("Can't log the decision to {}: {}", path, e);
             }
-            GameEvent::CardPicked(card_idx) => {
+        }
 
-                // interception
-                let tags = self.get_board().hand.cards[card_idx].tags.clone();
-                let interference = self.get_board_mut().kaiju_zone.cards.iter_mut()
-                    .filter_map(|k| if !k.stunned &&
-                                       k.intercepts_left > 0 &&
-                                       tags.contains(&k.intercept?.tag) {
-                                            Some(k)
-                                        } else {
-                                            None
-                                        })
-                    .next();
-                
-                if let Some(mut annoyance) = interference {
-                    annoyance.intercepts_left -= 1;
-                    self.get_board_mut().hand.remove(card_idx);
+        if settings.panel {
+            let summary = explanation.summary(board);
+            self.get_board_mut().explained.push(summary);
+        }
+    }
 
-                    return self.take_turn()
-                }
+    /// Resolves the effect stack item by item. Before an item resolves for the first time, cards in play
+    /// may respond to it; the responses go on top of it and resolve first.
+    /// When the stack is empty, the turn continues according to the current phase.
+    fn resolve_stack(&mut self) -> Box<dyn AutomatonState> {
+        while let Some(item) = self.get_board_mut().stack.pop() {
+            let mut item = match self.get_board_mut().take_responses(item) {
+                Some(item) => item,
+                None => continue // responses go first
+            };
 
-                // play the card
-                let card_target = self.get_board().hand.cards[card_idx].target_zone;
-                match card_target {
-                    BoardZone::None => {
-                        self.get_board_mut().play_card(card_idx);
-                        self.take_turn()
+            if !item.intercept_checked {
+                // every kaiju that can intercept is asked, until one does
+                while let Some(kaiju_idx) = self.get_board().interceptor_for(&item) {
+                    // kaiju sent by the other player obey them; the rest intercept whenever they can
+                    match self.get_board().kaiju_zone.cards[kaiju_idx].owner {
+                        None => self.intercept(kaiju_idx, &mut item, true),
+                        Some(owner) => {
+                            let owner_board = self.controller.get_board(owner);
+                            match owner_board.player.control {
+                                PlayerControl::Human => {
+                                    let question = format!("{}: let {}\nintercept {}?",
+                                                           owner_board.player.name,
+                                                           self.get_board().kaiju_zone.cards[kaiju_idx].name,
+                                                           item.source.name);
+                                    self.get_board_mut().stack.push(item);
+                                    return PromptState::new(
+                                        Box::new(take(self)),
+                                        owner,
+                                        question,
+                                        vec!(("Intercept".to_string(), GameEvent::InterceptDecided(kaiju_idx, true)),
+                                             ("Let through".to_string(), GameEvent::InterceptDecided(kaiju_idx, false))));
+                                },
+                                PlayerControl::AI => {
+                                    let ai = owner_board.ai.as_ref().expect("AI for AI player not loaded");
+                                    let decision = ai.strategy().intercept(&self.observation(owner), &self.get_board().kaiju_zone.cards[kaiju_idx], &item);
+                                    self.intercept(kaiju_idx, &mut item, decision);
+                                }
+                            }
+                        }
+                    }
+                }
+                item.intercept_checked = true;
+            }
+
+            if let Some((reactor, candidates)) = self.next_reactor(&mut item) {
+                let reacting_board = self.controller.get_board(reactor);
+                match reacting_board.player.control {
+                    PlayerControl::Human => {
+                        self.get_board_mut().stack.push(item.clone());
+                        return ReactionState::new(Box::new(take(self)), reactor, item);
                     },
+                    PlayerControl::AI => {
+                        let ai = reacting_board.ai.as_ref().expect("AI for AI player not loaded");
+                        let choice = ai.strategy().react(&self.observation(reactor), &item, &candidates);
+                        self.react(reactor, item, choice);
+                        continue;
+                    }
+                }
+            }
+
+            self.get_board_mut().resolve_item(item);
+
+            if let Some(state) = self.ask_decision() {
+                return state;
+            }
+        }
+
+        match self.phase {
+            TurnPhase::Starting | TurnPhase::Main => {
+                self.phase = TurnPhase::Main;
+                self.take_turn()
+            },
+            TurnPhase::Ending => {
+                self.get_board_mut().finish_turn();
+                self.controller.event(GameEvent::EndTurn)
+            }
+        }
+    }
+
+    /// Plays the card from hand, first letting the player choose how to pay its extra cost if it has one
+    fn pay_and_play(&mut self, card_idx: usize, target: Option<(BoardZone, usize)>) -> Box<dyn AutomatonState> {
+        let extra_cost = match self.get_board().hand.cards[card_idx].extra_cost {
+            Some(extra_cost) => extra_cost,
+            None => {
+                self.get_board_mut().play_card(card_idx, target, None);
+                return self.resolve_stack();
+            }
+        };
+
+        let options = self.get_board().extra_cost_options(card_idx, target);
+        if options.is_empty() {
+            println!("Cannot play, nothing to pay the extra cost with");
+            return self.resolve_stack();
+        }
+
+        match self.get_board().player.control {
+            PlayerControl::Human => ExtraCostState::new(Box::new(take(self)), card_idx, target, extra_cost, options),
+            PlayerControl::AI => {
+                let ai = self.get_board().ai.as_ref().expect("AI for AI player not loaded");
+                let payment = ai.strategy().pay_extra_cost(&self.observation(self.board_idx), extra_cost, &options);
+                self.event(GameEvent::CostPaid(card_idx, target, payment))
+            }
+        }
+    }
+
+    /// Lets the player on turn make the decision the last resolved effect asked for. Human players get
+    /// a state to decide in; AI players decide immediately and None is returned.
+    fn ask_decision(&mut self) -> Option<Box<dyn AutomatonState>> {
+        let decision = self.get_board().pending.clone()?;
+
+        match decision {
+            Decision::Arrange{whose, n, rearrange} => {
+                let cards = self.deck_of(whose).peek(n);
+                match self.get_board().player.control {
+                    PlayerControl::Human => Some(ArrangeState::new(Box::new(take(self)), cards, rearrange)),
+                    PlayerControl::AI => {
+                        let ai = self.get_board().ai.as_ref().expect("AI for AI player not loaded");
+                        let (top, bottom) = ai.strategy().arrange(&self.observation(self.board_idx), &cards, whose == Whose::Own);
+                        self.arranged(&top, &bottom);
+                        None
+                    }
+                }
+            },
+            Decision::Choose{options, picks} if picks >= options.len() => {
+                self.chosen(&(0..options.len()).collect::<Vec<usize>>()); // nothing to choose from
+                None
+            },
+            Decision::Choose{options, picks} => {
+                match self.get_board().player.control {
+                    PlayerControl::Human => Some(ChoiceState::new(Box::new(take(self)), options, picks)),
+                    PlayerControl::AI => {
+                        let ai = self.get_board().ai.as_ref().expect("AI for AI player not loaded");
+                        let chosen = ai.strategy().choose(&self.observation(self.board_idx), &options, picks);
+                        self.chosen(&chosen);
+                        None
+                    }
+                }
+            }
+        }
+    }
+
+    /// Carries out the chosen options of a modal effect
+    fn chosen(&mut self, chosen: &[usize]) {
+        let board = self.get_board_mut();
+        if let Some(Decision::Choose{options, ..}) = board.pending.take() {
+            board.continue_with(Mode::chosen_effects(&options, chosen));
+        }
+    }
+
+    /// Puts the revealed cards where the player decided
+    fn arranged(&mut self, top: &[usize], bottom: &[usize]) {
+        if let Some(Decision::Arrange{whose, n, rearrange}) = self.get_board_mut().pending.take() {
+            let deck = self.deck_of(whose);
+            if rearrange {
+                deck.arrange_top(n, top, bottom);
+                if whose == Whose::Opponent {
+                    deck.forget_order(); // the owner didn't see it
+                }
+            } else if whose == Whose::Own {
+                deck.reveal_top(n);
+            }
+
+            if whose == Whose::Opponent {
+                let seen = if rearrange { top.len() } else { n };
+                let peeked = self.get_opponent_mut().deck.peek(seen);
+                self.get_board_mut().peeked = peeked;
+            }
+        }
+    }
+
+    fn deck_of(&mut self, whose: Whose) -> &mut Deck {
+        match whose {
+            Whose::Own => &mut self.get_board_mut().deck,
+            Whose::Opponent => &mut self.get_opponent_mut().deck,
+        }
+    }
+
+    /// Carry out (or decline) interception of the item by the kaiju, and tell both players about it
+    fn intercept(&mut self, kaiju_idx: usize, item: &mut StackItem, intercepting: bool) {
+        let notice = self.get_board_mut().intercept(kaiju_idx, item, intercepting);
+        self.get_opponent_mut().log_notice(notice);
+    }
+
+    /// Finds the player that can react to the item with a card from their hand, unless they were asked already,
+    /// and marks them as asked. A kaiju strike is answered by the player it strikes, anything else by the other player.
+    fn next_reactor(&self, item: &mut StackItem) -> Option<(usize, Vec<usize>)> {
+        if item.answer || item.countered {
+            return None;
+        }
+
+        let reactor = if item.trigger == Trigger::Strike { self.board_idx } else { self.opo_idx };
+        if item.offered.contains(&reactor) {
+            return None;
+        }
+        item.offered.push(reactor);
+
+        let candidates = self.controller.get_board(reactor).reactions_to(item);
+        if candidates.is_empty() {
+            None
+        } else {
+            Some((reactor, candidates))
+        }
+    }
+
+    /// Plays the reaction card (if any was chosen) and puts the item back on the stack. Reactions of the player
+    /// on turn go to the stack on top of the item, reactions of the other player resolve on their board right away.
+    fn react(&mut self, reactor: usize, mut item: StackItem, card_idx: Option<usize>) {
+        if let Some(card_idx) = card_idx {
+            let board = self.controller.get_board_mut(reactor);
+            let card = board.hand.remove(card_idx).expect("Reacting with card not in hand");
+            let reaction = card.reaction.clone().expect("Reacting with card that has no reaction");
+
+            println!("  {} reacts to {} with {}", board.player.name, item.source.name, card.name);
+            if reaction.counter {
+                item.countered = true;
+            }
+
+            let reacting = StackItem::answering(item.trigger, BoardZone::Hand, card, reaction.effects);
+            if reactor == self.board_idx {
+                let board = self.get_board_mut();
+                board.stack.push(item);
+                board.stack.push(reacting);
+                return;
+            }
+
+            board.stack.push(reacting);
+            board.resolve_stack();
+        }
+
+        self.get_board_mut().stack.push(item);
+    }
+}
+
+impl AutomatonState for GameplayState {
+    fn event(&mut self, event: GameEvent) -> Box<dyn AutomatonState> {
+        println!("GameplayState received event: {:?}", event);
+        self.record(&event);
+
+        match event {
+            GameEvent::StartTurn => {
+                self.phase = TurnPhase::Starting;
+                self.get_board_mut().begin_turn();
+                self.resolve_stack()
+            }
+            GameEvent::CardPicked(card_idx) => {
+                if !self.get_board().can_play(card_idx) {
+                    println!("Cannot play, nothing to pay the extra cost with");
+                    return self.resolve_stack();
+                }
+
+                // play the card (interception happens when it is about to resolve)
+                let card_target = self.get_board().hand.cards[card_idx].target_zone;
+                match card_target {
+                    BoardZone::None => self.pay_and_play(card_idx, None),
                     _ => {
                         self.get_board_mut().update_availability();
                         match self.get_board().player.control {
                             PlayerControl::Human => TargetingState::new(Box::new(take(self)), BoardZone::Hand, card_idx, card_target),
                             PlayerControl::AI => {
-                                let board = self.get_board_mut();
-                                let ai = board.ai.as_ref().expect("AI for AI player not loaded");
-                                let intent = ai.target_card(board, card_idx, card_target);
-                                self.event(intent)
+                                let ai = self.get_board().ai.as_ref().expect("AI for AI player not loaded");
+                                let intent = ai.strategy().target_card(&self.observation(self.board_idx), card_idx, card_target);
+                                self.ai_acts(intent)
                             } 
                         }
                         
@@ -298,39 +1307,62 @@ impl AutomatonState for GameplayState {
             }, 
             GameEvent::CardTargeted(card_zone, card_idx, target_zone, target_idx) => {
                 if target_zone != BoardZone::None {
-                    self.get_board_mut().play_card_on_target(card_idx, target_zone, target_idx);
-                };
-                self.take_turn()
+                    self.pay_and_play(card_idx, Some((target_zone, target_idx)))
+                } else {
+                    self.resolve_stack()
+                }
+            },
+            GameEvent::CardActivated(idx) => {
+                if self.get_board().buildings.cards[idx].can_activate(&self.get_board().globals) {
+                    self.get_board_mut().activate(idx);
+                } else {
+                    println!("Cannot activate, already used or too expensive");
+                }
+                self.resolve_stack()
+            },
+            GameEvent::CostPaid(card_idx, target, payment) => {
+                if self.get_board().extra_cost_options(card_idx, target).contains(&payment) {
+                    self.get_board_mut().play_card(card_idx, target, Some(payment));
+                } else {
+                    println!("Cannot pay the extra cost with that");
+                }
+                self.resolve_stack()
             },
             GameEvent::CardBought(zone, card_idx) => {
-
-                // TODO extract as method to GameBoard? I may want to use it in computing is_available, too
-                let can_afford = {
-                    let store = self.get_board_mut().store_by_zone(zone);
-                    let card_cost = store.menu.cards[card_idx].cost.clone();
-                    self.get_board().globals.can_afford(&card_cost)
-                };
-
-                if can_afford {
-                    let store = self.get_board_mut().store_by_zone(zone);
-                    let card = store.buy_card(card_idx);
-
-                    self.get_board_mut().globals.pay(&card.cost);
-
-                    if card.give_to_enemy {
-                        self.get_opponent_mut().deck.add(card.clone());
-                    } else {
-                        self.get_board_mut().deck.add(card.clone());
+                if self.get_board().can_buy(zone, card_idx) {
+                    if let Some(mut card) = self.get_board_mut().buy_card(zone, card_idx) {
+                        card.owner = Some(self.board_idx);
+                        self.get_opponent_mut().receive(card);
                     }
                 } else {
                     println!("Cannot buy, relevant global value too low (i.e. you do not have enough cash)")
                 }
 
-                self.take_turn()
+                self.resolve_stack()
             }
             GameEvent::EndTurn => {
+                self.phase = TurnPhase::Ending;
                 self.get_board_mut().end_turn();
-                self.controller.event(GameEvent::EndTurn)
+                self.resolve_stack()
+            }
+            GameEvent::Chosen(chosen) => {
+                self.chosen(&chosen);
+                self.resolve_stack()
+            }
+            GameEvent::Arranged(top, bottom) => {
+                self.arranged(&top, &bottom);
+                self.resolve_stack()
+            }
+            GameEvent::InterceptDecided(kaiju_idx, intercepting) => {
+                let mut item = self.get_board_mut().stack.pop().expect("Interception of nothing");
+                self.intercept(kaiju_idx, &mut item, intercepting);
+                self.get_board_mut().stack.push(item);
+                self.resolve_stack()
+            }
+            GameEvent::Reacted(reactor, card_idx) => {
+                let item = self.get_board_mut().stack.pop().expect("Reaction to nothing");
+                self.react(reactor, item, card_idx);
+                self.resolve_stack()
             }
             GameEvent::GameEnded => Box::new(GameEndedState {}),
             _ => {
@@ -343,3 +1375,548 @@ impl AutomatonState for GameplayState {
         Box::new(take(self))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game_objects::fixtures::*;
+
+    fn responding(name: &str, trigger: Trigger, counter: bool, effects: Vec<Effect>) -> Card {
+        let mut card = Card{
+            responses: vec!(Response{ trigger, tag: None, counter, effects, times: None }),
+            ..named(name)
+        };
+        card.reset();
+        card
+    }
+
+    fn reacting(name: &str, trigger: Trigger) -> Card {
+        Card{ reaction: Some(Response{ trigger, tag: None, counter: false, effects: Vec::new(), times: None }), ..named(name) }
+    }
+
+    /// Game of the two boards, the first one on turn
+    fn gameplay(boards: Vec<BoardState>) -> GameplayState {
+        let controller = GameControlState::new(boards, Assets::default(), Default::default(), Deck::default(),
+            Default::default(), Default::default(), None);
+        GameplayState{ controller: Box::new(controller), board_idx: 0, opo_idx: 1, phase: TurnPhase::Main }
+    }
+
+    fn killer() -> Card {
+        Card{ target_zone: BoardZone::Kaiju, target_effect: TargetEffect::Kill, ..named("killer") }
+    }
+
+    #[test]
+    fn response_resolves_before_the_item() {
+        let wall = responding("wall", Trigger::Strike, false, vec!(Effect::Global{key: Globals::Block, val: 1}));
+        let kaiju = Card{ on_strike: vec!(Effect::Break), ..named("kaiju") };
+        let mut board = board(Vec::new(), vec!(wall), vec!(kaiju));
+
+        board.end_turn();
+        board.resolve_stack();
+
+        assert_eq!(names(&board.buildings.cards), ["wall"]);
+    }
+
+    #[test]
+    fn countering_response_cancels_the_played_card() {
+        let counter = responding("counter", Trigger::Play, true, Vec::new());
+        let played = Card{ on_play: vec!(Effect::Points{val: 3}), ..named("played") };
+        let mut board = board(vec!(played), Vec::new(), vec!(counter));
+
+        board.play_card(0, None, None);
+        board.resolve_stack();
+
+        assert_eq!(board.points, 0);
+        assert!(board.hand.empty());
+    }
+
+    #[test]
+    fn target_is_found_after_it_moves() {
+        let mut board = board(vec!(killer()), Vec::new(), vec!(named("k0"), named("k1")));
+
+        board.play_card(0, Some((BoardZone::Kaiju, 1)), None);
+        board.kaiju_zone.remove(0);
+        board.resolve_stack();
+
+        assert!(board.kaiju_zone.empty());
+    }
+
+    #[test]
+    fn card_fizzles_when_its_target_is_gone() {
+        let mut board = board(vec!(killer()), Vec::new(), vec!(named("k0"), named("k1")));
+
+        board.play_card(0, Some((BoardZone::Kaiju, 1)), None);
+        board.kaiju_zone.remove(1);
+        board.resolve_stack();
+
+        assert_eq!(names(&board.kaiju_zone.cards), ["k0"]);
+    }
+
+    #[test]
+    fn strike_reactions_are_offered_to_the_struck_player() {
+        let on_turn = board(vec!(named("a"), reacting("dodge", Trigger::Strike)), Vec::new(), Vec::new());
+        let other = board(vec!(reacting("dodge", Trigger::Strike)), Vec::new(), Vec::new());
+        let game = gameplay(vec!(on_turn, other));
+        let mut strike = StackItem::new(Trigger::Strike, BoardZone::Kaiju, named("kaiju"), vec!(Effect::Break));
+
+        assert_eq!(game.next_reactor(&mut strike), Some((0, vec!(1))));
+        assert_eq!(game.next_reactor(&mut strike), None);
+    }
+
+    #[test]
+    fn play_reactions_are_offered_to_the_other_player() {
+        let on_turn = board(vec!(reacting("dodge", Trigger::Play)), Vec::new(), Vec::new());
+        let other = board(vec!(reacting("dodge", Trigger::Strike), reacting("deny", Trigger::Play)), Vec::new(), Vec::new());
+        let game = gameplay(vec!(on_turn, other));
+        let mut played = StackItem::new(Trigger::Play, BoardZone::Hand, named("played"), Vec::new());
+
+        assert_eq!(game.next_reactor(&mut played), Some((1, vec!(1))));
+    }
+
+    #[test]
+    fn every_kaiju_that_can_intercept_is_asked_until_one_does() {
+        let intercept = Some(Intercept{ tag: Tag::Sea, times: 1 });
+        let mut sent = Card{ intercept, owner: Some(1), ..named("sent") };
+        let mut wild = Card{ intercept, ..named("wild") };
+        sent.reset();
+        wild.reset();
+        // the sent kaiju lets the card through, as it only hurts the player who plays it
+        let played = Card{ tags: vec!(Tag::Sea), on_play: vec!(Effect::Break), ..named("harmful") };
+        let mut board = board(vec!(played), vec!(named("house")), vec!(sent, wild));
+
+        board.play_card(0, None, None);
+        board.resolve_stack();
+
+        assert_eq!(names(&board.buildings.cards), ["house"]);
+        assert_eq!(board.kaiju_zone.cards[0].intercepts_left, 1);
+        assert_eq!(board.kaiju_zone.cards[1].intercepts_left, 0);
+    }
+
+    #[test]
+    fn blocked_break_defends_and_unblocked_destroys() {
+        let house = Card{ on_defend: vec!(Effect::Points{val: 1}), on_destroyed: vec!(Effect::Points{val: 2}), ..named("house") };
+        let mut board = board(Vec::new(), vec!(house), Vec::new());
+        board.globals.add(Globals::Block, 1);
+
+        board.evaluate_effect(&Effect::Break, named("kaiju"));
+        board.resolve_stack();
+        assert_eq!(board.points, 1);
+
+        board.evaluate_effect(&Effect::Break, named("kaiju"));
+        board.resolve_stack();
+        assert_eq!(board.points, 3);
+        assert!(board.buildings.empty());
+    }
+
+    #[test]
+    fn drawn_and_discarded_cards_trigger() {
+        let lucky = Card{ on_drawn: vec!(Effect::Points{val: 1}), ..named("lucky") };
+        let junk = Card{ on_discarded: vec!(Effect::Points{val: 2}), ..named("junk") };
+        let played = Card{ extra_cost: Some(ExtraCost::Discard), ..named("played") };
+        let mut board = board(vec!(played, junk), Vec::new(), Vec::new());
+        *board.deck = Deck::from(vec!(lucky));
+
+        board.draw_card();
+        board.resolve_stack();
+        assert_eq!(board.points, 1);
+
+        board.play_card(0, None, Some(1));
+        board.resolve_stack();
+        assert_eq!(board.points, 3);
+    }
+
+    #[test]
+    fn arriving_kaiju_triggers_cards_in_play() {
+        let watchtower = Card{ on_kaiju_arrives: vec!(Effect::Points{val: 1}), ..named("watchtower") };
+        let mut board = board(Vec::new(), vec!(watchtower), Vec::new());
+
+        board.add_kaiju(named("kaiju"));
+        board.resolve_stack();
+
+        assert_eq!(board.points, 1);
+    }
+
+    fn factory(cards: Vec<Card>) -> Rc<CardFactory> {
+        Rc::new(cards.into_iter().map(|card| (card.name.clone(), card)).collect())
+    }
+
+    #[test]
+    fn spawned_cards_that_do_not_fit_to_hand_go_to_the_deck() {
+        let mut board = board(vec!(named("a")), Vec::new(), Vec::new());
+        board.hand.size = Some(2);
+        board.card_factory = factory(vec!(named("egg")));
+
+        board.evaluate_effect(&Effect::Spawn{card: "egg".to_string(), zone: BoardZone::Hand, count: 2}, named("queen"));
+
+        assert_eq!(names(&board.hand.cards), ["a", "egg"]);
+        assert_eq!(names(board.deck.iter()), ["egg"]);
+        assert_ne!(board.hand.cards[1].id, board.deck.iter().next().unwrap().id);
+    }
+
+    #[test]
+    fn transformed_card_stays_in_place_and_keeps_its_owner() {
+        let egg = Card{ owner: Some(1), ..named("egg") };
+        let mut board = board(Vec::new(), Vec::new(), vec!(named("k0"), egg.clone()));
+        board.card_factory = factory(vec!(named("hatchling")));
+
+        board.evaluate_effect(&Effect::Transform{into: "hatchling".to_string()}, egg);
+
+        assert_eq!(names(&board.kaiju_zone.cards), ["k0", "hatchling"]);
+        assert_eq!(board.kaiju_zone.cards[1].owner, Some(1));
+    }
+
+    #[test]
+    fn auras_apply_while_their_source_is_in_play() {
+        let radar = Card{
+            auras: vec!(Aura{ affects: BoardZone::Kaiju, tag: None, modifier: Modifier::NoIntercept{tag: Tag::Air} }),
+            ..named("radar")
+        };
+        let kaiju = Card{ intercept: Some(Intercept{ tag: Tag::Air, times: 1 }), ..named("kaiju") };
+        let plane = Card{ tags: vec!(Tag::Air), ..named("plane") };
+        let mut board = board(Vec::new(), vec!(radar), vec!(kaiju));
+
+        board.apply_auras();
+        assert!(!board.kaiju_zone.cards[0].can_intercept(&plane));
+
+        board.destroy_building(0);
+        board.apply_auras();
+        board.kaiju_zone.cards[0].reset();
+        assert!(board.kaiju_zone.cards[0].can_intercept(&plane));
+    }
+
+    fn escalating(grow: Vec<Effect>, leaves_after: Option<u16>) -> Card {
+        let escalation = Escalation{ grow, leaves_after, rampage_at: None, on_rampage: Vec::new() };
+        Card{ on_strike: vec!(Effect::Break), escalation: Some(escalation), ..named("kaiju") }
+    }
+
+    #[test]
+    fn kaiju_grows_every_turn_it_starts_in_play() {
+        let mut board = board(Vec::new(), Vec::new(), vec!(escalating(vec!(Effect::Break), None)));
+
+        board.begin_turn();
+        assert_eq!(board.kaiju_zone.cards[0].strike_effects(), [Effect::Break, Effect::Break]);
+
+        board.begin_turn();
+        assert_eq!(board.kaiju_zone.cards[0].strike_effects().len(), 3);
+    }
+
+    #[test]
+    fn kaiju_leaves_after_its_strikes() {
+        let mut board = board(Vec::new(), Vec::new(), vec!(escalating(Vec::new(), Some(2))));
+        let id = board.kaiju_zone.cards[0].id;
+
+        board.after_strike(id);
+        assert_eq!(board.kaiju_zone.cards[0].strikes, 1);
+
+        board.after_strike(id);
+        assert!(board.kaiju_zone.empty());
+    }
+
+    #[test]
+    fn kaiju_leaving_the_zone_starts_afresh() {
+        let mut board = board(Vec::new(), Vec::new(), vec!(escalating(vec!(Effect::Break), Some(3))));
+        board.begin_turn();
+        board.after_strike(board.kaiju_zone.cards[0].id);
+
+        board.pay_extra_cost(ExtraCost::ReturnKaiju, 0);
+
+        let returned = board.deck.iter().next().unwrap();
+        assert_eq!((returned.turns_in_play, returned.strikes), (0, 0));
+        assert_eq!(returned.strike_effects(), [Effect::Break]);
+    }
+
+    #[test]
+    fn countdown_triggers_once_its_turns_are_up() {
+        let timer = Card{ countdown: Some(2), on_countdown: vec!(Effect::Points{val: 1}), ..named("timer") };
+        let mut board = board(Vec::new(), vec!(timer), Vec::new());
+
+        board.begin_turn();
+        board.resolve_stack();
+        assert_eq!(board.points, 0);
+
+        board.begin_turn();
+        board.resolve_stack();
+        assert_eq!(board.points, 1);
+    }
+
+    fn full_zone(overflow: KaijuOverflow) -> BoardState {
+        let weak = Card{ on_strike: vec!(Effect::Break), ..named("weak") };
+        let strong = Card{ on_strike: vec!(Effect::BreakUnblockable), ..named("strong") };
+        let mut board = board(Vec::new(), vec!(named("house"), named("shop")), vec!(strong, weak));
+        board.kaiju_zone.size = Some(2);
+        board.kaiju_overflow = overflow;
+        board
+    }
+
+    #[test]
+    fn overflowing_kaiju_goes_to_the_bottom() {
+        let mut board = full_zone(KaijuOverflow::Bottom);
+
+        board.add_kaiju(named("new"));
+
+        assert_eq!(names(&board.kaiju_zone.cards), ["strong", "weak"]);
+        assert_eq!(names(board.deck.iter()), ["new"]);
+    }
+
+    #[test]
+    fn overflowing_kaiju_strikes_and_goes_to_the_bottom() {
+        let mut board = full_zone(KaijuOverflow::Strike);
+
+        board.add_kaiju(Card{ on_strike: vec!(Effect::Break), ..named("new") });
+        board.resolve_stack();
+
+        assert_eq!(names(&board.kaiju_zone.cards), ["strong", "weak"]);
+        assert_eq!(names(&board.buildings.cards), ["shop"]);
+        assert_eq!(names(board.deck.iter()), ["new"]);
+    }
+
+    #[test]
+    fn overflowing_kaiju_replaces_the_weakest() {
+        let mut board = full_zone(KaijuOverflow::ReplaceWeakest);
+
+        board.add_kaiju(named("new"));
+
+        assert_eq!(names(&board.kaiju_zone.cards), ["strong", "new"]);
+        assert_eq!(names(board.deck.iter()), ["weak"]);
+    }
+
+    #[test]
+    fn received_cards_go_where_their_placement_says() {
+        let mut board = board(vec!(named("a")), Vec::new(), Vec::new());
+        board.hand.size = Some(2);
+        *board.deck = deck_of(&["b", "c"]);
+
+        board.receive(Card{ placement: Placement::Top, ..named("top") });
+        board.receive(Card{ placement: Placement::Bottom, ..named("bottom") });
+        board.receive(Card{ placement: Placement::Kaiju, draw_to: BoardZone::Kaiju, ..named("kaiju") });
+        board.receive(Card{ placement: Placement::Hand, ..named("gift") });
+        board.receive(Card{ placement: Placement::Hand, ..named("late gift") });
+
+        assert_eq!(names(&board.hand.cards), ["a", "gift"]);
+        assert_eq!(names(&board.kaiju_zone.cards), ["kaiju"]);
+        assert_eq!(names(board.deck.iter()), ["top", "b", "c", "bottom", "late gift"]);
+    }
+
+    #[test]
+    fn bounced_kaiju_goes_where_the_card_says() {
+        let bounce = Card{ target_zone: BoardZone::Kaiju, target_effect: TargetEffect::Bounce, bounce_to: Placement::Top, ..named("bounce") };
+        let mut board = board(vec!(bounce), Vec::new(), vec!(named("kaiju")));
+        *board.deck = deck_of(&["a"]);
+
+        board.play_card(0, Some((BoardZone::Kaiju, 0)), None);
+        board.resolve_stack();
+
+        assert!(board.kaiju_zone.empty());
+        assert_eq!(names(board.deck.iter()), ["kaiju", "a"]);
+    }
+
+    /// Game where the player on turn resolves the effect, waiting for their decision
+    fn deciding(effect: Effect, own: &[&str], opponent: &[&str]) -> GameplayState {
+        let mut game = gameplay(vec!(board(Vec::new(), Vec::new(), Vec::new()), board(Vec::new(), Vec::new(), Vec::new())));
+        *game.get_board_mut().deck = deck_of(own);
+        *game.get_opponent_mut().deck = deck_of(opponent);
+        game.get_board_mut().resolve_item(StackItem::new(Trigger::Play, BoardZone::Hand, named("looking"), vec!(effect)));
+        game
+    }
+
+    /// Do the cards stay on top and at the bottom, however the deck's owner imagines the rest of it?
+    fn known_ends(deck: &Deck, top: &[&str], bottom: &[&str]) -> bool {
+        let strings = |cards: &[&str]| cards.iter().map(|name| name.to_string()).collect::<Vec<String>>();
+        seed_rng(4);
+        (0..20).all(|_| {
+            let mut imagined = deck.clone();
+            imagined.shuffle_unknown();
+            let imagined = names(imagined.iter());
+            imagined.starts_with(&strings(top)) && imagined.ends_with(&strings(bottom))
+        })
+    }
+
+    #[test]
+    fn scry_reorders_the_top_and_puts_the_rest_to_the_bottom() {
+        let mut game = deciding(Effect::Scry{n: 3}, &["a", "b", "c", "d", "e", "f"], &[]);
+
+        game.arranged(&[2, 0], &[1]);
+
+        let deck = &game.get_board().deck;
+        assert_eq!(names(deck.iter()), ["c", "a", "d", "e", "f", "b"]);
+        assert!(known_ends(deck, &["c", "a"], &["b"]));
+        assert!(!known_ends(deck, &["c", "a", "d"], &["b"]));
+    }
+
+    #[test]
+    fn peek_at_own_deck_makes_the_top_known() {
+        let mut game = deciding(Effect::Peek{n: 2, whose: Whose::Own}, &["a", "b", "c", "d", "e"], &[]);
+
+        game.arranged(&[], &[]);
+
+        let deck = &game.get_board().deck;
+        assert_eq!(names(deck.iter()), ["a", "b", "c", "d", "e"]);
+        assert!(known_ends(deck, &["a", "b"], &[]));
+    }
+
+    #[test]
+    fn peek_at_opponent_deck_is_remembered_by_us_only() {
+        let mut game = deciding(Effect::Peek{n: 2, whose: Whose::Opponent}, &[], &["x", "y", "z", "w", "v"]);
+
+        game.arranged(&[], &[]);
+
+        assert_eq!(names(&game.get_board().peeked), ["x", "y"]);
+        let deck = &game.get_opponent().deck;
+        assert_eq!(names(deck.iter()), ["x", "y", "z", "w", "v"]);
+        assert!(!known_ends(deck, &["x", "y"], &[]));
+    }
+
+    fn modes(points: &[i16]) -> Vec<Mode> {
+        points.iter().map(|val| Mode{ text: String::new(), effects: vec!(Effect::Points{val: *val}) }).collect()
+    }
+
+    #[test]
+    fn chosen_modes_resolve_before_the_rest_of_the_card() {
+        let mut game = gameplay(vec!(board(Vec::new(), Vec::new(), Vec::new()), board(Vec::new(), Vec::new(), Vec::new())));
+        let effects = vec!(Effect::Choose{options: modes(&[1, 10, 100]), picks: 2}, Effect::Points{val: 1000});
+        game.get_board_mut().resolve_item(StackItem::new(Trigger::Play, BoardZone::Hand, named("modal"), effects));
+        assert_eq!(game.get_board().points, 0);
+
+        game.chosen(&[2, 0]);
+        game.get_board_mut().resolve_stack();
+
+        assert_eq!(game.get_board().points, 1101);
+        assert!(game.get_board().pending.is_none());
+    }
+
+    #[test]
+    fn boards_not_on_turn_choose_automatically() {
+        let mut board = board(Vec::new(), Vec::new(), Vec::new());
+        board.stack.push(StackItem::new(Trigger::Play, BoardZone::Hand, named("modal"), vec!(Effect::Choose{options: modes(&[1, 5]), picks: 1})));
+
+        board.resolve_stack();
+
+        assert_eq!(board.points, 5);
+    }
+
+    fn with_extra_cost(name: &str, extra_cost: ExtraCost, target_zone: BoardZone) -> Card {
+        Card{ extra_cost: Some(extra_cost), target_zone, target_effect: TargetEffect::Kill, ..named(name) }
+    }
+
+    #[test]
+    fn discarding_card_after_the_played_one() {
+        let played = with_extra_cost("played", ExtraCost::Discard, BoardZone::None);
+        let mut board = board(vec!(named("a"), played, named("c")), Vec::new(), Vec::new());
+
+        board.play_card(1, None, Some(2));
+
+        assert_eq!(names(&board.hand.cards), ["a"]);
+    }
+
+    #[test]
+    fn discarding_card_before_the_played_one() {
+        let played = with_extra_cost("played", ExtraCost::Discard, BoardZone::None);
+        let mut board = board(vec!(named("a"), played, named("c")), Vec::new(), Vec::new());
+
+        board.play_card(1, None, Some(0));
+
+        assert_eq!(names(&board.hand.cards), ["c"]);
+    }
+
+    #[test]
+    fn returned_kaiju_before_the_target_does_not_spare_it() {
+        let bait = with_extra_cost("bait", ExtraCost::ReturnKaiju, BoardZone::Kaiju);
+        let mut board = board(vec!(bait), Vec::new(), vec!(named("k0"), named("k1"), named("k2")));
+
+        board.play_card(0, Some((BoardZone::Kaiju, 2)), Some(0));
+        board.resolve_stack();
+
+        assert_eq!(names(&board.kaiju_zone.cards), ["k1"]);
+        assert_eq!(names(board.deck.iter()), ["k0"]);
+    }
+
+    #[test]
+    fn sacrificed_building_is_destroyed_before_the_card_resolves() {
+        let house = Card{ on_destroyed: vec!(Effect::Points{val: 1}), ..named("house") };
+        let ritual = Card{ extra_cost: Some(ExtraCost::Sacrifice), on_play: vec!(Effect::Points{val: 10}), ..named("ritual") };
+        let mut board = board(vec!(ritual), vec!(named("shop"), house), Vec::new());
+
+        board.play_card(0, None, Some(1));
+        board.resolve_stack();
+
+        assert_eq!(names(&board.buildings.cards), ["shop"]);
+        assert_eq!(board.points, 11);
+    }
+
+    #[test]
+    fn target_cannot_pay_for_itself() {
+        let bait = with_extra_cost("bait", ExtraCost::ReturnKaiju, BoardZone::Kaiju);
+        let one_kaiju = board(vec!(bait.clone()), Vec::new(), vec!(named("k0")));
+
+        assert!(!one_kaiju.can_play(0));
+        assert!(!one_kaiju.legal_actions().iter().any(|action| matches!(action, GameEvent::CardTargeted(..))));
+
+        let two_kaiju = board(vec!(bait), Vec::new(), vec!(named("k0"), named("k1")));
+        assert!(two_kaiju.can_play(0));
+        assert_eq!(two_kaiju.payable_targets(0), [0, 1]);
+    }
+
+    #[test]
+    fn building_is_activated_once_per_turn_for_its_cost() {
+        let ability = Ability{ cost: Cost{ currency: Globals::Build, count: 2 }, effects: vec!(Effect::Points{val: 1}) };
+        let factory = Card{ activated: Some(ability), ..named("factory") };
+        let mut board = board(Vec::new(), vec!(factory), Vec::new());
+        board.globals.add(Globals::Build, 5);
+
+        assert!(board.buildings.cards[0].can_activate(&board.globals));
+        board.apply_action(&GameEvent::CardActivated(0));
+        assert_eq!(board.points, 1);
+        assert_eq!(board.globals.get(Globals::Build), 3);
+        assert!(!board.buildings.cards[0].can_activate(&board.globals));
+
+        board.begin_turn();
+        assert!(board.buildings.cards[0].can_activate(&board.globals));
+    }
+
+    #[test]
+    fn score_counts_our_cards_and_defeated_kaiju() {
+        let house = Card{ points: 1, ..named("house") };
+        let kaiju = Card{ points: 5, draw_to: BoardZone::Kaiju, ..named("kaiju") };
+        let mut board = board(vec!(killer(), house.clone()), vec!(house), vec!(kaiju.clone()));
+        *board.deck = Deck::from(vec!(kaiju, Card{ points: 2, ..named("wall") }));
+        assert_eq!(board.score(), 4);
+
+        board.play_card(0, Some((BoardZone::Kaiju, 0)), None);
+        board.resolve_stack();
+        board.evaluate_effect(&Effect::Points{val: 3}, named("parade"));
+
+        assert_eq!(board.score(), 12);
+    }
+
+    #[test]
+    fn simulated_decision_goes_to_the_panel_but_not_the_log() {
+        let log = std::env::temp_dir().join("simulated_decision_is_not_logged.txt");
+        let _ = std::fs::remove_file(&log);
+        let settings = crate::ai::ExplanationSettings{ log_file: Some(log.to_string_lossy().into_owned()), panel: true };
+        let boards = vec!(board(vec!(named("card")), Vec::new(), Vec::new()), board(Vec::new(), Vec::new(), Vec::new()));
+        let controller = GameControlState::new(boards, Assets::default(), Default::default(), Deck::default(),
+            settings, Default::default(), None);
+        let mut state = GameplayState{ controller: Box::new(controller), board_idx: 0, opo_idx: 1, phase: TurnPhase::Main };
+
+        state.explained(&Explanation::new(GameEvent::CardPicked(0), "why not", Vec::new()));
+
+        assert_eq!(state.get_board().explained, ["play card: why not"]);
+        assert!(!log.exists());
+    }
+
+    #[test]
+    fn hint_is_what_the_ai_would_play_without_playing_it() {
+        let parade = Card{ on_play: vec!(Effect::Points{val: 2}), ..named("parade") };
+        let mut human = board(vec!(named("dud"), parade), vec!(named("house")), Vec::new());
+        human.start_turn();
+        let mut state = gameplay(vec!(human, board(Vec::new(), Vec::new(), Vec::new())));
+        assert!(state.hint().is_none());
+
+        state.get_board_mut().ai = Some(AI::new(crate::ai::AiConfig::Greedy, &Default::default()));
+        let hint = state.hint().expect("Hint from the AI");
+
+        assert!(matches!(hint.action, GameEvent::CardPicked(1)));
+        assert_eq!(state.get_board().hand.cards.len(), 2);
+        assert_eq!(state.get_board().score(), 0);
+    }
+}
@@ -1,16 +1,17 @@
 extern crate quicksilver;
 extern crate json;
 
-use rand::thread_rng;
+use rand::{Rng, SeedableRng, FromEntropy};
+use rand::rngs::StdRng;
 use rand::seq::SliceRandom;
 use quicksilver::prelude::*;
 use std::collections::VecDeque;
 use std::collections::HashMap;
 use serde_derive::*;
 use itertools::Itertools;
-use itertools::izip;
-use std::iter;
 use std::fmt;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::cell::RefCell;
 use crate::game_logic::BoardState;
 
 pub struct GameData {
@@ -22,14 +23,89 @@ pub struct GameData {
 pub enum Effect {
     Echo{msg: String},
     Global{key: Globals, val: i16},
-    Return,
+    Return{#[serde(default)] to: Placement},
     ToBuildings,
     Break,
     BreakUnblockable,
     BreakEverything,
+    Spawn{card: String, zone: BoardZone, #[serde(default = "one")] count: u8},
+    Transform{into: String},
+    Peek{n: u8, #[serde(default)] whose: Whose}, // look at top cards of a deck
+    Scry{n: u8}, // look at top cards of own deck, reorder them or put them to the bottom
+    Choose{options: Vec<Mode>, #[serde(default = "one")] picks: u8}, // player picks which bundles of effects happen
+    Points{val: i16}, // victory points
     None,
 }
 
+/// One of the options of a modal ("choose one") effect
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Mode {
+    #[serde(default)]
+    pub text: String,
+    pub effects: Vec<Effect>,
+}
+
+impl Mode {
+    /// All effects of the chosen options, in the order they were chosen
+    pub fn chosen_effects(options: &[Mode], chosen: &[usize]) -> Vec<Effect> {
+        chosen.iter()
+            .filter_map(|idx| options.get(*idx))
+            .flat_map(|mode| mode.effects.iter().cloned())
+            .collect()
+    }
+}
+
+impl fmt::Display for Mode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.text.is_empty() {
+            write!(f, "{:?}", self.effects)
+        } else {
+            write!(f, "{}", self.text)
+        }
+    }
+}
+
+impl Effect {
+    /// Decision the player has to make when the effect resolves, for effects that need one
+    pub fn decision(&self) -> Option<Decision> {
+        match self {
+            Effect::Peek{n, whose} => Some(Decision::Arrange{whose: *whose, n: *n as usize, rearrange: false}),
+            Effect::Scry{n} => Some(Decision::Arrange{whose: Whose::Own, n: *n as usize, rearrange: true}),
+            Effect::Choose{options, picks} => Some(Decision::Choose{options: options.clone(), picks: *picks as usize}),
+            _ => None
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize, Default)]
+pub enum Whose {
+    #[default]
+    Own,
+    Opponent,
+}
+
+/// Choice a player has to make in the middle of resolving an item. The rest of the stack waits until it is made.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Decision {
+    Arrange{whose: Whose, n: usize, rearrange: bool}, // top n cards of the deck are revealed (and maybe rearranged)
+    Choose{options: Vec<Mode>, picks: usize},
+}
+
+/// Where a card ends up when it is given, bought or returned
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize, Default)]
+pub enum Placement {
+    Hand,
+    Kaiju, // directly to the kaiju zone, skipping the deck
+    Top,
+    #[default]
+    Bottom,
+    Shuffle, // somewhere in the deck
+}
+
+fn one() -> u8 {
+    1
+}
+
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub enum TargetEffect {
     None,
@@ -88,6 +164,35 @@ impl Default for Cost {
     }
 }
 
+/// Something the player must give up, besides paying the cost, to play a card
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ExtraCost {
+    Sacrifice, // destroy one of own buildings
+    Discard, // discard another card from hand
+    ReturnKaiju, // return a kaiju from own kaiju zone to the bottom of the deck
+}
+
+impl ExtraCost {
+    /// Zone the card given up comes from
+    pub fn zone(&self) -> BoardZone {
+        match self {
+            ExtraCost::Sacrifice => BoardZone::Buildings,
+            ExtraCost::Discard => BoardZone::Hand,
+            ExtraCost::ReturnKaiju => BoardZone::Kaiju,
+        }
+    }
+}
+
+impl fmt::Display for ExtraCost {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ExtraCost::Sacrifice => write!(f, "Sacrifice a building"),
+            ExtraCost::Discard => write!(f, "Discard a card"),
+            ExtraCost::ReturnKaiju => write!(f, "Return a kaiju\nto your deck"),
+        }
+    }
+}
+
 #[derive(Copy, Clone, Debug, Hash, PartialEq, Eq, Serialize, Deserialize)]
 pub enum BoardZone {
     None,
@@ -124,6 +229,123 @@ pub struct Intercept {
     pub times: u8
 }
 
+/// Continuous effect of an aura on the cards it affects
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "modifier")]
+pub enum Modifier {
+    NoIntercept{tag: Tag}, // affected kaiju can't intercept cards with this tag
+    Cost{val: i16}, // affected cards cost this much more (or less, when negative)
+    BeforeStrike{effects: Vec<Effect>}, // affected kaiju resolve these effects before their on_strike effects
+}
+
+impl fmt::Display for Modifier {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Modifier::NoIntercept{tag} => write!(f, "Can't intercept {:?}", tag),
+            Modifier::Cost{val} => write!(f, "Cost {:+}", val),
+            Modifier::BeforeStrike{effects} => write!(f, "Strikes with {} more", effects.len()),
+        }
+    }
+}
+
+/// Static ability of a card: applies a modifier to cards in a zone of the same board, as long as the card
+/// with the aura is in play (in buildings or kaiju zone).
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Aura {
+    pub affects: BoardZone,
+    #[serde(default)]
+    pub tag: Option<Tag>, // only cards with this tag are affected
+    pub modifier: Modifier,
+}
+
+impl Aura {
+    pub fn affects_card(&self, zone: BoardZone, card: &Card) -> bool {
+        self.affects == zone &&
+            match self.tag {
+                Some(tag) => card.tags.contains(&tag),
+                None => true
+            }
+    }
+}
+
+/// What happens to a kaiju arriving to a full kaiju zone
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize, Default)]
+pub enum KaijuOverflow {
+    #[default]
+    Bottom, // goes to the bottom of the deck
+    Strike, // strikes right away, then goes to the bottom of the deck
+    ReplaceWeakest, // the weakest kaiju in the zone goes to the bottom of the deck instead
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KaijuZoneRules {
+    pub size: usize,
+    pub overflow: KaijuOverflow,
+}
+
+/// How a kaiju changes while it stays in play
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct Escalation {
+    pub grow: Vec<Effect>, // added to strike effects for every turn the kaiju survives
+    pub leaves_after: Option<u16>, // number of strikes, after which the kaiju leaves play
+    pub rampage_at: Option<u16>, // number of strikes, after which on_rampage effects are triggered
+    pub on_rampage: Vec<Effect>,
+}
+
+/// Ability a card in play can use once per turn, paying the cost
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct Ability {
+    pub cost: Cost,
+    pub effects: Vec<Effect>,
+}
+
+/// What caused an item to be put on the effect stack. Responses are matched against this.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Trigger {
+    Play,
+    TurnStart,
+    TurnEnd,
+    Strike,
+    Defend,
+    Destroyed,
+    Bought,
+    Drawn,
+    Discarded,
+    KaijuArrives,
+    Countdown,
+    Rampage,
+    Activate,
+}
+
+/// Ability of a card in play to react to an item on the effect stack before it resolves.
+/// Responding effects resolve before the item; a countering response cancels the item.
+///
+/// Interception is a special case of this: it counters a played card with a given tag.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Response {
+    pub trigger: Trigger,
+    #[serde(default)]
+    pub tag: Option<Tag>,
+    #[serde(default)]
+    pub counter: bool,
+    #[serde(default)]
+    pub effects: Vec<Effect>,
+    #[serde(default)]
+    pub times: Option<u8>, // per turn; None means unlimited
+}
+
+impl Response {
+    pub fn matches(&self, item: &StackItem) -> bool {
+        self.trigger == item.trigger &&
+            match self.tag {
+                Some(tag) => item.source.tags.contains(&tag),
+                None => true
+            }
+    }
+}
+
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, Default)]
 #[serde(default)]
 pub struct Card {
@@ -133,11 +355,21 @@ pub struct Card {
     pub on_turn_start: Vec<Effect>,
     pub on_turn_end: Vec<Effect>,
     pub on_strike: Vec<Effect>,
-    //pub on_defend: Vec<Effect>,
+    pub on_defend: Vec<Effect>,
+    pub on_destroyed: Vec<Effect>,
+    pub on_bought: Vec<Effect>,
+    pub on_drawn: Vec<Effect>,
+    pub on_discarded: Vec<Effect>,
+    pub on_kaiju_arrives: Vec<Effect>,
+    pub countdown: Option<u16>, // number of turns in play before on_countdown effects are triggered
+    pub on_countdown: Vec<Effect>,
     pub cost: Cost,
+    pub extra_cost: Option<ExtraCost>, // paid when the card is played
     pub target_zone: BoardZone,
     pub target_effect: TargetEffect,
+    pub bounce_to: Placement, // where the target of Bounce goes
     pub give_to_enemy: bool,
+    pub placement: Placement, // where the card goes when bought
     
     #[serde(default = "no_image")]
     pub image: String,
@@ -147,24 +379,208 @@ pub struct Card {
 
     pub tags: Vec<Tag>,
 
+    pub points: i16, // victory points while the card is ours; for kaiju, points for defeating it
+
     pub intercept: Option<Intercept>,
+    pub intercept_immune: bool,
+    pub bypass: Vec<Tag>, // kaiju with any of these tags can't intercept this card
+
+    pub responses: Vec<Response>,
+
+    pub reaction: Option<Response>, // can be played from hand during other player's turn or when our kaiju strike; "times" is ignored
+
+    pub auras: Vec<Aura>,
+
+    pub escalation: Option<Escalation>,
+
+    pub activated: Option<Ability>, // used by clicking the card in play
 
     #[serde(skip)]
     pub stunned: bool,
     #[serde(skip)]
+    pub exhausted: bool, // activated ability was used this turn
+    #[serde(skip)]
     pub intercepts_left: u8,
     #[serde(skip)]
+    pub responses_left: Vec<Option<u8>>,
+    #[serde(skip)]
     pub available: bool,    
+    #[serde(skip)]
+    pub owner: Option<usize>, // board index of the player that sent this card to its current board
+    #[serde(skip)]
+    pub id: usize, // unique for each instance of card in the game; 0 for templates
+    #[serde(skip)]
+    pub turns_in_play: u16,
+    #[serde(skip)]
+    pub strikes: u16,
+    #[serde(skip)]
+    pub grown: u16, // turns the kaiju grew: its strike has escalation.grow effects this many times over
+    #[serde(skip)]
+    pub modifiers: Vec<Modifier>, // applied by auras of cards in play; recomputed whenever the board changes
+}
+
+static NEXT_CARD_ID: AtomicUsize = AtomicUsize::new(1);
+
+thread_local! {
+    // all randomness of the game comes from here, so that simulated games can be repeated
+    static RNG: RefCell<StdRng> = RefCell::new(StdRng::from_entropy());
+}
+
+/// Makes the game deterministic from now on (as long as the AI isn't limited by time)
+pub fn seed_rng(seed: u64) {
+    RNG.with(|rng| *rng.borrow_mut() = StdRng::seed_from_u64(seed));
+}
+
+pub fn with_rng<T>(f: impl FnOnce(&mut StdRng) -> T) -> T {
+    RNG.with(|rng| f(&mut rng.borrow_mut()))
 }
 
+static NO_EFFECTS: Vec<Effect> = Vec::new();
+
+pub type CardFactory = HashMap<String, Card>;
+
 fn no_image() -> String {
     "none.png".to_string()
 }
 
 impl Card {
+    /// Creates a new copy of the card (usually a template from card factory or a store) that can be told apart
+    /// from other copies.
+    pub fn instantiate(&self) -> Card {
+        let mut card = self.clone();
+        card.id = NEXT_CARD_ID.fetch_add(1, Ordering::Relaxed);
+        card.leave_play();
+        card
+    }
+
     pub fn reset(&mut self) {
         self.stunned = false;
-        self.intercepts_left = match &self.intercept {Some(i) => i.times, None => 0}
+        self.intercepts_left = match &self.intercept {Some(i) => i.times, None => 0};
+        self.responses_left = self.responses.iter().map(|r| r.times).collect();
+        self.exhausted = false;
+    }
+
+    /// Can the activated ability be used now?
+    pub fn can_activate(&self, globals: &NumberMap) -> bool {
+        match &self.activated {
+            Some(ability) => !self.exhausted && !self.stunned && globals.can_afford(&ability.cost),
+            None => false
+        }
+    }
+
+    /// Effects linked to the trigger
+    pub fn effects_on(&self, trigger: Trigger) -> &Vec<Effect> {
+        match trigger {
+            Trigger::Play => &self.on_play,
+            Trigger::TurnStart => &self.on_turn_start,
+            Trigger::TurnEnd => &self.on_turn_end,
+            Trigger::Strike => &self.on_strike,
+            Trigger::Defend => &self.on_defend,
+            Trigger::Destroyed => &self.on_destroyed,
+            Trigger::Bought => &self.on_bought,
+            Trigger::Drawn => &self.on_drawn,
+            Trigger::Discarded => &self.on_discarded,
+            Trigger::KaijuArrives => &self.on_kaiju_arrives,
+            Trigger::Countdown => &self.on_countdown,
+            Trigger::Rampage => match &self.escalation {
+                Some(escalation) => &escalation.on_rampage,
+                None => &NO_EFFECTS
+            },
+            Trigger::Activate => match &self.activated {
+                Some(ability) => &ability.effects,
+                None => &NO_EFFECTS
+            },
+        }
+    }
+
+    /// Can this (kaiju) card intercept the played card right now?
+    pub fn can_intercept(&self, played: &Card) -> bool {
+        let prevented = self.modifiers.iter().any(|m| match m {
+            Modifier::NoIntercept{tag} => played.tags.contains(tag),
+            _ => false
+        });
+
+        match self.intercept {
+            Some(intercept) => !self.stunned &&
+                               !prevented &&
+                               self.intercepts_left > 0 &&
+                               !played.intercept_immune &&
+                               played.tags.contains(&intercept.tag) &&
+                               !self.tags.iter().any(|t| played.bypass.contains(t)),
+            None => false
+        }
+    }
+
+    /// Rough estimate how dangerous the card (a kaiju) is
+    pub fn threat(&self) -> i16 {
+        let strike: i16 = self.strike_effects().iter()
+            .map(|e| match e {
+                Effect::Break => 1,
+                Effect::BreakUnblockable => 2,
+                Effect::BreakEverything => 5,
+                Effect::Spawn{count, ..} => *count as i16,
+                _ => 0
+            })
+            .sum();
+        let growth = match &self.escalation {
+            Some(escalation) => escalation.grow.len() as i16,
+            None => 0
+        };
+
+        strike + growth
+    }
+
+    /// Cost after modifiers are applied. It is never negative.
+    pub fn effective_cost(&self) -> Cost {
+        let change: i16 = self.modifiers.iter()
+            .map(|m| match m {
+                Modifier::Cost{val} => *val,
+                _ => 0
+            })
+            .sum();
+
+        Cost{ count: (self.cost.count + change).max(0), currency: self.cost.currency }
+    }
+
+    /// Effects of the card's strike, including those added by modifiers and by growing
+    pub fn strike_effects(&self) -> Vec<Effect> {
+        let growth = match &self.escalation {
+            Some(escalation) => (0..self.grown).flat_map(|_| escalation.grow.iter().cloned()).collect(),
+            None => vec!()
+        };
+
+        self.modifiers.iter()
+            .flat_map(|m| match m {
+                Modifier::BeforeStrike{effects} => effects.clone(),
+                _ => vec!()
+            })
+            .chain(self.on_strike.iter().cloned())
+            .chain(growth)
+            .collect()
+    }
+
+    /// Forgets what happened to the card in play: the turns, the strikes and the growth
+    pub fn leave_play(&mut self) {
+        self.turns_in_play = 0;
+        self.strikes = 0;
+        self.grown = 0;
+    }
+
+    /// Uses up one of the response's charges for this turn. Returns false if there are none left.
+    pub fn use_response(&mut self, response_idx: usize) -> bool {
+        if self.responses_left.len() != self.responses.len() {
+            // card entered play mid-turn and was never reset
+            return false;
+        }
+
+        match self.responses_left[response_idx] {
+            None => true,
+            Some(0) => false,
+            Some(times) => {
+                self.responses_left[response_idx] = Some(times - 1);
+                true
+            }
+        }
     }
 }
 
@@ -195,7 +611,7 @@ impl CardContainer {
 
     pub fn is_full(&self) -> bool {
         match self.size {
-            Some(size) =>  self.cards.len() == size,
+            Some(size) =>  self.cards.len() >= size,
             None => false
         }
         
@@ -206,24 +622,25 @@ impl CardContainer {
     }
 
     // Safe remove
+    /// Takes the card out of the container. Whatever happened to it in play is forgotten.
     pub fn remove(&mut self, card_idx: usize) -> Option<Card> {
         if self.cards.len() > 0 {
-            Some(self.cards.remove(card_idx))
+            let mut card = self.cards.remove(card_idx);
+            card.leave_play();
+            Some(card)
         } else {
             None
         }
     }
 
-    /// Extract effects linked to speciffied event for each card in the container (effects can repeat). 
-    /// Join these lists together, tagging every effect with the card that causes it and zone the card belongs to.
+    /// Extract effects linked to speciffied trigger for each card in the container, wrapping them in a stack item
+    /// tagged with the card that causes them and zone the card belongs to. Cards without such effects are skipped.
     ///
     /// Expected use: to evaluate events (on_turn_start effects etc.)
-    pub fn all_effects(&self, event_selector: fn(&Card)-> &Vec<Effect>) -> Vec<(BoardZone, Card, Effect)> {
-
+    pub fn triggered(&self, trigger: Trigger) -> Vec<StackItem> {
         self.cards.iter()
-                .flat_map(|c| izip!(iter::repeat(self.zone), 
-                                    iter::repeat(c).cloned(),
-                                    event_selector(c).iter().cloned()) )
+                .filter(|c| !c.effects_on(trigger).is_empty())
+                .map(|c| StackItem::new(trigger, self.zone, c.clone(), c.effects_on(trigger).clone()))
                 .collect()
     }
 }
@@ -235,39 +652,217 @@ impl PartialEq for CardContainer {
 }
 impl Eq for CardContainer {}
 
+/// Cards face down, drawn from the top. The owner knows where some of them are: those they have seen put on
+/// the top or the bottom since the last shuffle.
 #[derive(Serialize, Deserialize, Debug, Clone, Default)]
 pub struct Deck {
-    cards: VecDeque<Card>
+    cards: VecDeque<Card>,
+    #[serde(default)]
+    known_top: usize, // number of cards on top whose order the owner knows
+    #[serde(default)]
+    known_bottom: usize,
 }
 
 impl Deck {
     pub fn new() -> Self {
-        Self{ cards: VecDeque::new() }
+        Self{ cards: VecDeque::new(), known_top: 0, known_bottom: 0 }
     }
 
     pub fn draw(&mut self) -> Option<Card> {
-        self.cards.pop_front()
+        let card = self.cards.pop_front()?;
+        if self.known_top > 0 {
+            self.known_top -= 1;
+        } else if self.known_bottom > self.cards.len() {
+            self.known_bottom -= 1; // there were no unknown cards left
+        }
+        Some(card)
     }
 
+    /// Puts the card to the bottom of the deck
     pub fn add(&mut self, new_card: Card) {
-        self.cards.push_back(new_card)
+        self.cards.push_back(new_card);
+        self.known_bottom += 1;
+    }
+
+    /// Copies of the top n cards, top first
+    pub fn peek(&self, n: usize) -> Vec<Card> {
+        self.cards.iter().take(n).cloned().collect()
+    }
+
+    /// Rearranges the top n cards (as returned by peek): cards at indices in `top` go back on top in the given order,
+    /// cards in `bottom` go to the bottom. Cards in neither stay on top, beneath those in `top`.
+    pub fn arrange_top(&mut self, n: usize, top: &[usize], bottom: &[usize]) {
+        let n = n.min(self.cards.len());
+        let revealed: Vec<Card> = self.cards.drain(..n).collect();
+
+        let bottom: Vec<usize> = bottom.iter().cloned().filter(|i| *i < n && !top.contains(i)).unique().collect();
+        let kept: Vec<usize> = top.iter().cloned()
+            .filter(|i| *i < n)
+            .chain((0..n).filter(|i| !top.contains(i) && !bottom.contains(i)))
+            .unique()
+            .collect();
+
+        for idx in kept.iter().rev() {
+            self.cards.push_front(revealed[*idx].clone());
+        }
+        for idx in bottom.iter() {
+            self.cards.push_back(revealed[*idx].clone());
+        }
+
+        // the revealed cards are known now, and so are the known ones beneath them
+        self.known_top = kept.len() + self.known_top.saturating_sub(n);
+        self.known_bottom += bottom.len();
+        self.limit_known();
+    }
+
+    pub fn add_top(&mut self, new_card: Card) {
+        self.cards.push_front(new_card);
+        self.known_top += 1;
+    }
+
+    /// Puts the card to a random position in the deck, without shuffling the rest
+    pub fn shuffle_in(&mut self, new_card: Card) {
+        let position = with_rng(|rng| rng.gen_range(0, self.cards.len() + 1));
+        self.cards.insert(position, new_card);
+        self.forget_order(); // it could be anywhere
     }
 
     pub fn shuffle(&mut self) {
         let mut card_pile: Vec<Card> = self.cards.drain(..).collect();
-        card_pile.shuffle(&mut thread_rng());
+        with_rng(|rng| card_pile.shuffle(rng));
         self.cards.clear();
         self.cards.extend(card_pile);
+        self.forget_order();
+    }
+
+    /// The owner saw the top n cards
+    pub fn reveal_top(&mut self, n: usize) {
+        self.known_top = self.known_top.max(n);
+        self.limit_known();
+    }
+
+    /// Someone else rearranged the deck; the owner doesn't know where its cards are anymore
+    pub fn forget_order(&mut self) {
+        self.known_top = 0;
+        self.known_bottom = 0;
+    }
+
+    /// Shuffles the cards the owner doesn't know the place of, leaving the deck as its owner can imagine it
+    pub fn shuffle_unknown(&mut self) {
+        let unknown = self.known_top..self.cards.len() - self.known_bottom;
+        let mut card_pile: Vec<Card> = self.cards.drain(unknown.clone()).collect();
+        with_rng(|rng| card_pile.shuffle(rng));
+        for (offset, card) in card_pile.into_iter().enumerate() {
+            self.cards.insert(unknown.start + offset, card);
+        }
+    }
+
+    fn limit_known(&mut self) {
+        self.known_top = self.known_top.min(self.cards.len());
+        self.known_bottom = self.known_bottom.min(self.cards.len() - self.known_top);
     }
 
     pub fn len(&self) -> usize {
         self.cards.len()
     }
+
+    pub fn iter(&self) -> impl Iterator<Item=&Card> {
+        self.cards.iter()
+    }
 }
 
 impl From<Vec<Card>> for Deck {
     fn from(source: Vec<Card>) -> Self {
-        Deck{ cards: VecDeque::<Card>::from(source) }
+        Deck{ cards: VecDeque::<Card>::from(source), known_top: 0, known_bottom: 0 }
+    }
+}
+
+/// A triggered ability (or a played card) waiting on the effect stack.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct StackItem {
+    pub trigger: Trigger,
+    pub zone: BoardZone, // where the source card was when the item was created
+    pub source: Card,
+    pub effects: Vec<Effect>,
+    pub target: Option<(BoardZone, usize)>, // zone and id of the targeted card
+    pub responded: bool, // response window was already opened for this item
+    pub offered: Vec<usize>, // boards (by index) whose players were offered to react to this item
+    pub answer: bool, // this item is itself a response or reaction; nobody can respond to it
+    pub intercept_checked: bool,
+    pub declined: Vec<usize>, // kaiju (by id) that let this item through instead of intercepting it
+    pub countered: bool,
+}
+
+impl StackItem {
+    pub fn new(trigger: Trigger, zone: BoardZone, source: Card, effects: Vec<Effect>) -> Self {
+        Self { trigger, zone, source, effects,
+               target: None,
+               responded: false,
+               offered: Vec::new(),
+               answer: false,
+               intercept_checked: false,
+               declined: Vec::new(),
+               countered: false }
+    }
+
+    /// Item created by a response or reaction to another item.
+    pub fn answering(trigger: Trigger, zone: BoardZone, source: Card, effects: Vec<Effect>) -> Self {
+        let mut item = Self::new(trigger, zone, source, effects);
+        item.responded = true;
+        item.answer = true;
+        item
+    }
+}
+
+/// Something that happened during play that the players should be told about
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Notice {
+    Intercepted{kaiju: String, card: String},
+    InterceptDeclined{kaiju: String, card: String},
+    Grows{kaiju: String},
+    NoRoom{kaiju: String},
+    Event{name: String},
+    Rampage{kaiju: String},
+    KaijuLeft{kaiju: String},
+}
+
+impl fmt::Display for Notice {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Notice::Intercepted{kaiju, card} => write!(f, "{} intercepted {}", kaiju, card),
+            Notice::InterceptDeclined{kaiju, card} => write!(f, "{} let {} through", kaiju, card),
+            Notice::Grows{kaiju} => write!(f, "{} grows stronger", kaiju),
+            Notice::NoRoom{kaiju} => write!(f, "No room for {}", kaiju),
+            Notice::Event{name} => write!(f, "This round: {}", name),
+            Notice::Rampage{kaiju} => write!(f, "{} goes on a rampage!", kaiju),
+            Notice::KaijuLeft{kaiju} => write!(f, "{} left", kaiju),
+        }
+    }
+}
+
+/// Items are resolved last in, first out: anything pushed while an item waits (responses, abilities
+/// triggered by resolving effects) is resolved before it.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct EffectStack {
+    items: Vec<StackItem>
+}
+
+impl EffectStack {
+    pub fn new() -> Self {
+        Self{ items: Vec::new() }
+    }
+
+    pub fn push(&mut self, item: StackItem) {
+        self.items.push(item)
+    }
+
+    /// Push several items at once, so that they resolve in the given order.
+    pub fn push_ordered(&mut self, items: Vec<StackItem>) {
+        self.items.extend(items.into_iter().rev())
+    }
+
+    pub fn pop(&mut self) -> Option<StackItem> {
+        self.items.pop()
     }
 }
 
@@ -348,7 +943,7 @@ pub struct Store {
 impl Store {
 
     pub fn buy_card(&mut self, card_idx: usize) -> Card {
-        let card = self.menu.get(card_idx);
+        let card = self.menu.get(card_idx).instantiate();
 
         if let StoreType::Drafted{size: _, from_deck: _} = self.store_type {
             self.menu.remove(card_idx);
@@ -365,3 +960,151 @@ impl Store {
         }
     }
 }
+
+/// Cards and boards for tests
+#[cfg(test)]
+pub mod fixtures {
+    use super::*;
+
+    /// A new instance of a card that has nothing but its name, drawn to hand like cards loaded without draw_to
+    pub fn named(name: &str) -> Card {
+        Card{ name: name.to_string(), draw_to: BoardZone::default_draw(), ..Default::default() }.instantiate()
+    }
+
+    pub fn names<'a>(cards: impl IntoIterator<Item = &'a Card>) -> Vec<String> {
+        cards.into_iter().map(|card| card.name.clone()).collect()
+    }
+
+    /// A deck of plain cards with the names, topmost first
+    pub fn deck_of(names: &[&str]) -> Deck {
+        Deck::from(names.iter().map(|name| named(name)).collect::<Vec<Card>>())
+    }
+
+    pub fn container(zone: BoardZone, cards: Vec<Card>) -> Box<CardContainer> {
+        Box::new(CardContainer{ zone, cards, size: None })
+    }
+
+    /// A quiet board with the cards in hand, buildings and the kaiju zone; everything else is empty
+    pub fn board(hand: Vec<Card>, buildings: Vec<Card>, kaiju: Vec<Card>) -> BoardState {
+        BoardState {
+            hand: container(BoardZone::Hand, hand),
+            buildings: container(BoardZone::Buildings, buildings),
+            kaiju_zone: container(BoardZone::Kaiju, kaiju),
+            simulated: true,
+            ..Default::default()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::fixtures::*;
+
+    #[test]
+    fn shuffled_in_card_can_end_up_anywhere_without_moving_the_rest() {
+        seed_rng(3);
+        let mut positions = Vec::new();
+        for _ in 0..30 {
+            let mut deck = deck_of(&["a", "b", "c"]);
+            deck.shuffle_in(named("new"));
+
+            let mut order = names(deck.iter());
+            let position = order.iter().position(|name| name == "new").unwrap();
+            order.remove(position);
+            assert_eq!(order, ["a", "b", "c"]);
+            positions.push(position);
+        }
+
+        assert!((0..4).all(|position| positions.contains(&position)));
+    }
+
+    fn assert_known_fits(deck: &Deck) {
+        assert!(deck.known_top + deck.known_bottom <= deck.len(),
+                "{} known on top and {} at the bottom of {} cards", deck.known_top, deck.known_bottom, deck.len());
+    }
+
+    #[test]
+    fn shuffle_unknown_keeps_cards_put_on_top_and_bottom() {
+        seed_rng(1);
+        let mut deck = deck_of(&["a", "b", "c", "d", "e", "f", "g", "h"]);
+        deck.add_top(named("top"));
+        deck.add(named("bottom"));
+
+        for _ in 0..20 {
+            let mut imagined = deck.clone();
+            imagined.shuffle_unknown();
+            let imagined = names(imagined.iter());
+            assert_eq!(imagined[0], "top");
+            assert_eq!(imagined[9], "bottom");
+        }
+    }
+
+    #[test]
+    fn arrange_top_makes_the_arranged_cards_known() {
+        seed_rng(2);
+        let mut deck = deck_of(&["a", "b", "c", "d", "e", "f", "g", "h", "i", "j"]);
+        deck.arrange_top(3, &[2, 0], &[1]);
+
+        assert_eq!((deck.known_top, deck.known_bottom), (2, 1));
+        let arranged = names(deck.iter());
+        assert_eq!(arranged[..2], ["c", "a"]);
+        assert_eq!(arranged[9], "b");
+
+        for _ in 0..20 {
+            let mut imagined = deck.clone();
+            imagined.shuffle_unknown();
+            let imagined = names(imagined.iter());
+            assert_eq!(imagined[..2], arranged[..2]);
+            assert_eq!(imagined[9], arranged[9]);
+        }
+    }
+
+    #[test]
+    fn arrange_top_keeps_known_cards_beneath_known() {
+        let mut deck = deck_of(&["a", "b", "c", "d", "e", "f"]);
+        deck.reveal_top(4);
+        deck.arrange_top(2, &[1], &[0]);
+
+        assert_eq!((deck.known_top, deck.known_bottom), (3, 1));
+        assert_known_fits(&deck);
+    }
+
+    #[test]
+    fn draw_uses_up_known_cards() {
+        let mut deck = deck_of(&["a", "b"]);
+        deck.add_top(named("top"));
+        deck.add(named("bottom"));
+
+        let mut drawn = Vec::new();
+        while let Some(card) = deck.draw() {
+            assert_known_fits(&deck);
+            drawn.push(card.name);
+        }
+        assert_eq!(drawn, ["top", "a", "b", "bottom"]);
+        assert_eq!((deck.known_top, deck.known_bottom), (0, 0));
+    }
+
+    #[test]
+    fn draw_reaches_known_bottom_cards() {
+        let mut deck = deck_of(&["a"]);
+        deck.add(named("bottom 1"));
+        deck.add(named("bottom 2"));
+
+        deck.draw();
+        assert_eq!(deck.known_bottom, 2);
+        deck.draw();
+        assert_eq!(deck.known_bottom, 1);
+        assert_known_fits(&deck);
+    }
+
+    #[test]
+    fn shuffle_forgets_known_cards() {
+        let mut deck = deck_of(&["a", "b", "c", "d"]);
+        deck.add_top(named("top"));
+        deck.add(named("bottom"));
+        deck.shuffle();
+
+        assert_eq!((deck.known_top, deck.known_bottom), (0, 0));
+    }
+}
@@ -0,0 +1,11 @@
+extern crate quicksilver;
+pub mod automaton;
+pub mod ui;
+pub mod game_objects;
+pub mod game_logic;
+pub mod game_control;
+pub mod ai;
+pub mod loading;
+pub mod simulation;
+pub mod observation;
+pub mod analysis;
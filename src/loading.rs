@@ -8,7 +8,7 @@ use std::iter;
 use crate::game_logic::{BoardState};
 use crate::game_control::*;
 use crate::game_objects::*;
-use crate::ai::AI;
+use crate::ai::{AI, AiConfig, AiPacing, ExplanationSettings, Personality};
 use crate::automaton::{AutomatonState, GameEvent};
 use std::mem::take;
 use futures::{Async};
@@ -28,14 +28,12 @@ pub struct Assets {
     pub images: HashMap<String, Rc<Image>>, // UI cards do hold reference to images
 }
 
-type CardFactory = HashMap<String, Card>;
-
-
 fn cards_by_counts(factory: &CardFactory, counts: HashMap<String, usize>) -> Vec<Card> {
     counts.iter()
+        .sorted() // the same cards in the same order every time, so that seeded games repeat
         .flat_map(|(key, num)| iter::repeat(key).take(*num))
         .filter_map(|key| factory.get(key))
-        .cloned()
+        .map(Card::instantiate)
         .collect()
 }
 
@@ -125,7 +123,63 @@ pub fn load_players(json: &serde_json::Value) -> Vec<Player> {
     players
 }
 
-pub fn load_board(json: &serde_json::Value, card_factory: &CardFactory, player: Player) -> BoardState {
+/// Alternative ways to end the game, if the game data has any
+pub fn load_victory(json: &serde_json::Value) -> VictoryRules {
+    match json.get("victory") {
+        Some(node) => serde_json::from_value(node.clone()).expect("Malformed victory rules"),
+        None => VictoryRules::default()
+    }
+}
+
+/// What to do with explanations of AI decisions; nothing, unless the game data says otherwise
+pub fn load_explanation_settings(json: &serde_json::Value) -> ExplanationSettings {
+    match json.get("ai_explanations") {
+        Some(node) => serde_json::from_value(node.clone()).expect("Malformed AI explanation settings"),
+        None => ExplanationSettings::default()
+    }
+}
+
+/// How fast AI players play in the UI
+pub fn load_ai_pacing(json: &serde_json::Value) -> AiPacing {
+    match json.get("ai_pacing") {
+        Some(node) => serde_json::from_value(node.clone()).expect("Malformed AI pacing"),
+        None => AiPacing::default()
+    }
+}
+
+/// The AI that looks for mistakes of the players when the game is over; one looking ahead, unless the game says otherwise.
+/// There is none when the game sets it to null.
+pub fn load_analyst(json: &serde_json::Value) -> Option<AiConfig> {
+    match json.get("analysis") {
+        Some(serde_json::Value::Null) => None,
+        Some(node) => Some(serde_json::from_value(node.clone()).expect("Malformed analysis AI")),
+        None => Some(AiConfig::Lookahead{ depth: 1 })
+    }
+}
+
+/// AI personalities by name, for scripted AI players
+pub fn load_personalities(json: &serde_json::Value) -> HashMap<String, Personality> {
+    match json.get("personalities") {
+        Some(node) => serde_json::from_value(node.clone()).expect("Malformed AI personalities"),
+        None => HashMap::new()
+    }
+}
+
+pub fn load_cards(json: &serde_json::Value) -> Rc<CardFactory> {
+    serde_json::from_value(
+        json.get("cards").expect("file should have \"cards\" node").clone()
+    ).map(Rc::new).expect("malformed card list")
+}
+
+/// Deck of global events, empty if the game has none
+pub fn load_event_deck(json: &serde_json::Value, card_factory: &CardFactory) -> Deck {
+    match json.get("event_deck") {
+        Some(_) => parse_deck(json, "event_deck", card_factory),
+        None => Deck::new()
+    }
+}
+
+pub fn load_board(json: &serde_json::Value, card_factory: &Rc<CardFactory>, player: Player) -> BoardState {
     let store_node = "build_store";
     let trade_row = "kaiju_store";
     let hand_size = 5;
@@ -142,13 +196,17 @@ pub fn load_board(json: &serde_json::Value, card_factory: &CardFactory, player:
 
     let buildings = container_counts(BoardZone::Buildings, &json, &player.starting_buildings, card_factory);
 
-    let kaiju = CardContainer::new(BoardZone::Kaiju);
-
-    let ai = match player.control {
-        PlayerControl::Human => None,
-        PlayerControl::AI => Some(AI::new())
+    // kaiju zone is unlimited, unless the game says otherwise
+    let kaiju_rules: Option<KaijuZoneRules> = json.get("kaiju_zone")
+        .map(|node| serde_json::from_value(node.clone()).expect("Malformed kaiju zone rules"));
+    let (kaiju, kaiju_overflow) = match kaiju_rules {
+        Some(rules) => (CardContainer::new_sized(BoardZone::Kaiju, rules.size), rules.overflow),
+        None => (CardContainer::new(BoardZone::Kaiju), KaijuOverflow::default())
     };
 
+    // human players get one too, for hints
+    let ai = Some(AI::new(player.ai.clone(), &load_personalities(json)));
+
     println!("Loading done");
 
     BoardState {
@@ -160,7 +218,17 @@ pub fn load_board(json: &serde_json::Value, card_factory: &CardFactory, player:
         stores: Box::new(vec!(build_store, kaiju_store)),
         buildings: Box::new(buildings),
         kaiju_zone: Box::new(kaiju),
-        ai: ai
+        kaiju_overflow,
+        points: 0,
+        event: None,
+        stack: Box::new(EffectStack::new()),
+        pending: None,
+        log: Vec::new(),
+        peeked: Vec::new(),
+        card_factory: card_factory.clone(),
+        ai,
+        simulated: false,
+        explained: Vec::new(),
     }
 }
 
@@ -188,6 +256,11 @@ pub fn load_board(json: &serde_json::Value, card_factory: &CardFactory, player:
 #[derivative(Debug)]
 pub struct LoadingState {
     board_states: Vec<BoardState>,
+    victory: VictoryRules,
+    event_deck: Deck,
+    explanations: ExplanationSettings,
+    pacing: AiPacing,
+    analyst: Option<Box<AI>>,
     image_names: Vec<String>,
     font_names: Vec<String>,
     #[derivative(Debug = "ignore")]
@@ -214,9 +287,7 @@ impl LoadingState {
         let json: serde_json::Value = serde_json::from_slice(file.as_slice())
             .expect("file should be proper JSON");
 
-        let cards: CardFactory = serde_json::from_value(
-            json.get("cards").expect("file should have \"cards\" node").clone()
-        ).expect("malformed card list");
+        let cards = load_cards(&json);
 
         let mut image_names = cards.values()
             .map(|v| v.image.clone())
@@ -246,6 +317,11 @@ impl LoadingState {
 
         Box::new(Self {
             board_states,
+            victory: load_victory(&json),
+            event_deck: load_event_deck(&json, &cards),
+            explanations: load_explanation_settings(&json),
+            pacing: load_ai_pacing(&json),
+            analyst: load_analyst(&json).map(|config| AI::new(config, &load_personalities(&json))),
             image_names,
             font_names,
             loading: Some(loading_images),
@@ -279,6 +355,11 @@ impl AutomatonState for LoadingState {
                         fonts: loaded_fonts,
                         images: loaded_images,
                     },
+                    self.victory.clone(),
+                    take(&mut self.event_deck),
+                    self.explanations.clone(),
+                    self.pacing.clone(),
+                    self.analyst.clone(),
                 )); // TODO async load board
                 control_state.overtake()
             }
@@ -1,18 +1,11 @@
 //#![windows_subsystem = "windows"]
 extern crate quicksilver;
-mod automaton;
-mod ui;
-mod game_objects;
-mod game_logic;
-mod game_control;
-mod ai;
-mod loading;
 
 use quicksilver::prelude::*;
 
-use automaton::Automaton;
-use loading::LoadingState;
-use ui::{WINDOW_SIZE_H, WINDOW_SIZE_W};
+use streaming_deckbuilder::automaton::Automaton;
+use streaming_deckbuilder::loading::LoadingState;
+use streaming_deckbuilder::ui::{WINDOW_SIZE_H, WINDOW_SIZE_W};
 
 struct Game {
     automaton: Automaton,
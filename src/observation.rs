@@ -0,0 +1,209 @@
+//! What one player knows about the game. AI players decide from this, never from the boards themselves,
+//! and it is all a remote client would need to be sent.
+
+use crate::game_logic::BoardState;
+use crate::game_objects::{Card, Deck, NumberMap, BoardZone, with_rng};
+use rand::seq::SliceRandom;
+use serde_derive::*;
+use std::ops::Deref;
+
+/// The player's own board as they can imagine it, and what everyone sees of the other boards.
+///
+/// The own board is a copy of the real one where cards the player can't know the place of are shuffled:
+/// those deep in their deck and in the store decks. Everything else on it is theirs to see, including
+/// the cards they saw on top of the opponent's deck (`peeked`), as long as those are still there.
+#[derive(Serialize, Debug, Clone)]
+pub struct Observation {
+    board: BoardState,
+    pub opponents: Vec<PublicBoard>,
+}
+
+/// The part of a board seen by everyone
+#[derive(Serialize, Debug, Clone)]
+pub struct PublicBoard {
+    pub name: String,
+    pub opponent_idx: usize,
+    pub hand_size: usize,
+    pub deck_size: usize,
+    pub buildings: Vec<Card>,
+    pub kaiju: Vec<Card>,
+    pub globals: NumberMap,
+    pub score: i16,
+    pub defeated: bool,
+}
+
+impl Observation {
+    /// What the player with the index knows
+    pub fn new(boards: &[BoardState], player: usize) -> Self {
+        let mut observation = Self::of_board(&boards[player]);
+        if let Some(opponent) = boards.get(observation.board.player.opponent_idx) {
+            observation.board.peeked = still_on_top(&observation.board.peeked, &opponent.deck);
+        }
+        observation.opponents = boards.iter()
+            .enumerate()
+            .filter(|(idx, _)| *idx != player)
+            .map(|(_, board)| PublicBoard::new(board))
+            .collect();
+        observation
+    }
+
+    /// What the owner of the board knows, not looking at the other boards
+    pub fn of_board(board: &BoardState) -> Self {
+        let mut board = board.clone();
+        shuffle_hidden(&mut board);
+
+        Self{ board, opponents: Vec::new() }
+    }
+
+    /// One way the game could be, as far as the player knows, to be played out: a simulation of the own board
+    /// with the hidden cards shuffled anew, and simulations of the other boards
+    pub fn determinize(&self) -> (BoardState, Vec<BoardState>) {
+        let mut own = self.board.simulation();
+        shuffle_hidden(&mut own);
+
+        let opponents = self.opponents.iter()
+            .map(|opponent| opponent.imagine(&own))
+            .collect();
+        (own, opponents)
+    }
+
+    pub fn board(&self) -> &BoardState {
+        &self.board
+    }
+}
+
+/// Shuffles the cards whose place the owner of the board doesn't know: the unknown part of their deck and the store decks
+fn shuffle_hidden(board: &mut BoardState) {
+    board.deck.shuffle_unknown();
+    for store in board.stores.iter_mut() {
+        if let Some(deck) = store.deck.as_mut() {
+            deck.shuffle();
+        }
+    }
+}
+
+/// The seen cards that are still on top of the deck: cards drawn since then are gone, and nothing is left
+/// after a shuffle
+fn still_on_top(seen: &[Card], deck: &Deck) -> Vec<Card> {
+    let remaining = (0..=seen.len())
+        .map(|drawn| &seen[drawn..])
+        .find(|rest| rest.len() <= deck.len() && rest.iter().zip(deck.iter()).all(|(a, b)| a.id == b.id))
+        .unwrap_or(&[]);
+    remaining.to_vec()
+}
+
+// strategies read the own board most of the time
+impl Deref for Observation {
+    type Target = BoardState;
+
+    fn deref(&self) -> &BoardState {
+        &self.board
+    }
+}
+
+impl PublicBoard {
+    pub fn new(board: &BoardState) -> Self {
+        Self {
+            name: board.player.name.clone(),
+            opponent_idx: board.player.opponent_idx,
+            hand_size: board.hand.cards.len(),
+            deck_size: board.deck.len(),
+            buildings: board.buildings.cards.clone(),
+            kaiju: board.kaiju_zone.cards.clone(),
+            globals: (*board.globals).clone(),
+            score: board.score(),
+            defeated: board.is_defeated(),
+        }
+    }
+
+    /// A simulation of a board that looks like this one from outside. Its hidden hand and deck are made up of
+    /// cards like those in our hand and deck, except for the cards we saw on top of its deck. Everything else
+    /// (stores, rules) is like on our board.
+    pub fn imagine(&self, own: &BoardState) -> BoardState {
+        let pool: Vec<&Card> = own.deck.iter()
+            .chain(own.hand.cards.iter())
+            .filter(|c| c.draw_to != BoardZone::Kaiju)
+            .collect();
+        let sample = |count: usize| -> Vec<Card> {
+            with_rng(|rng| (0..count).filter_map(|_| pool.choose(rng).map(|c| c.instantiate())).collect())
+        };
+
+        let mut board = own.simulation();
+        board.player.name = self.name.clone();
+        board.player.opponent_idx = self.opponent_idx;
+        board.ai = None;
+        board.peeked.clear();
+        board.log.clear();
+
+        board.hand.cards = sample(self.hand_size);
+        let mut deck = Deck::from(sample(self.deck_size.saturating_sub(own.peeked.len())));
+        for seen in own.peeked.iter().rev() {
+            deck.add_top(seen.clone());
+        }
+        *board.deck = deck;
+        board.buildings.cards = self.buildings.clone();
+        board.kaiju_zone.cards = self.kaiju.clone();
+        *board.globals = self.globals.clone();
+
+        board.points = 0;
+        board.points = self.score - board.score();
+        board
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game_objects::fixtures::*;
+    use crate::game_objects::seed_rng;
+
+    #[test]
+    fn opponent_is_imagined_from_what_everyone_sees() {
+        seed_rng(6);
+        let mut own = board(vec!(named("a")), vec!(named("house")), Vec::new());
+        *own.deck = deck_of(&["b", "c"]);
+        let mut opponent = board(vec!(named("x"), named("y")), vec!(Card{ points: 2, ..named("wall") }), vec!(named("kaiju")));
+        *opponent.deck = deck_of(&["seen", "z", "w", "v"]);
+        opponent.points = 3;
+        own.peeked = opponent.deck.peek(1);
+
+        let observation = Observation{ board: own.clone(), opponents: vec!(PublicBoard::new(&opponent)) };
+        let (_, imagined) = observation.determinize();
+        let imagined = &imagined[0];
+
+        assert_eq!(imagined.hand.cards.len(), 2);
+        assert_eq!(imagined.deck.len(), 4);
+        assert_eq!(names(imagined.deck.peek(1).iter()), ["seen"]);
+        assert!(imagined.hand.cards.iter().chain(imagined.deck.iter().skip(1))
+            .all(|card| ["a", "b", "c"].contains(&card.name.as_str())));
+        assert_eq!(names(&imagined.buildings.cards), ["wall"]);
+        assert_eq!(names(&imagined.kaiju_zone.cards), ["kaiju"]);
+        assert_eq!(imagined.score(), opponent.score());
+        assert!(imagined.simulated);
+    }
+
+    #[test]
+    fn peeked_cards_stay_known_until_drawn() {
+        let mut deck = deck_of(&["a", "b", "c", "d"]);
+        let seen = deck.peek(3);
+        assert_eq!(names(&still_on_top(&seen, &deck)), ["a", "b", "c"]);
+
+        deck.draw();
+        deck.draw();
+        assert_eq!(names(&still_on_top(&seen, &deck)), ["c"]);
+
+        deck.draw();
+        deck.draw();
+        assert!(still_on_top(&seen, &deck).is_empty());
+    }
+
+    #[test]
+    fn peeked_cards_are_forgotten_after_shuffle() {
+        let deck = deck_of(&["a", "b", "c", "d"]);
+        let seen = deck.peek(3);
+        let mut reordered = deck.peek(4);
+        reordered.rotate_left(3);
+
+        assert!(still_on_top(&seen, &Deck::from(reordered)).is_empty());
+    }
+}
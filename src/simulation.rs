@@ -0,0 +1,227 @@
+//! Games between AI players without any UI, and statistics over many of them. Used for balancing cards.
+//!
+//! The game is played the same way as in the UI, except for what only happens there: players can't react
+//! to the opponent's cards from hand, and nobody looks at the opponent's deck. Kaiju do intercept cards, but
+//! those sent by the opponent decide as the default AI would, not as the opponent's AI.
+
+use crate::automaton::GameEvent;
+use crate::game_control::{GameControlState, VictoryRules, winner};
+use crate::game_logic::BoardState;
+use crate::game_objects::Deck;
+use crate::loading::Assets;
+use crate::ai::{AI, AiPacing, ExplanationSettings};
+use crate::observation::Observation;
+use std::collections::{HashMap, BTreeMap};
+use std::fmt;
+
+const MAX_ACTIONS: usize = 100; // in a turn, in case the AI never ends it
+const MAX_ROUNDS: i16 = 100; // games without other limits end as a draw after this
+
+/// What happened in one game. Players are indexed by seat, the first one starting.
+#[derive(Debug, Default)]
+pub struct GameRecord {
+    pub players: Vec<String>,
+    pub winner: Option<usize>, // None for a draw
+    pub rounds: i16,
+    pub turns: u32,
+    pub bought: Vec<HashMap<String, u32>>, // card name -> times bought, for each player
+    pub played: Vec<HashMap<String, u32>>,
+}
+
+/// Plays the game to its end. All boards are played by their AI (the default one if they have none).
+pub fn play_game(boards: Vec<BoardState>, victory: VictoryRules, event_deck: Deck) -> GameRecord {
+    let mut record = GameRecord {
+        players: boards.iter().map(|b| b.player.name.clone()).collect(),
+        bought: vec![HashMap::new(); boards.len()],
+        played: vec![HashMap::new(); boards.len()],
+        ..GameRecord::default()
+    };
+
+    let mut control = GameControlState::new(boards, Assets::default(), victory, event_deck, ExplanationSettings::default(), AiPacing::default(), None);
+    for idx in 0..control.boards().len() {
+        control.get_board_mut(idx).simulated = true;
+    }
+    control.begin_round();
+
+    loop {
+        let idx = control.current_player();
+        play_turn(&mut control, idx, &mut record);
+        record.turns += 1;
+
+        if control.next_turn() {
+            record.winner = winner(control.boards());
+            break;
+        }
+        if control.round() >= MAX_ROUNDS {
+            break;
+        }
+    }
+
+    record.rounds = control.round();
+    record
+}
+
+fn play_turn(control: &mut GameControlState, idx: usize, record: &mut GameRecord) {
+    let opponent_idx = control.get_board(idx).player.opponent_idx;
+    control.get_board_mut(idx).start_turn();
+
+    for _ in 0..MAX_ACTIONS {
+        let observation = Observation::new(control.boards(), idx);
+        let board = control.get_board(idx);
+        let action = match &board.ai {
+            Some(ai) => ai.strategy().select_card(&observation),
+            None => AI::default().strategy().select_card(&observation)
+        };
+
+        match &action {
+            GameEvent::CardPicked(card) | GameEvent::CardTargeted(_, card, _, _) =>
+                count(&mut record.played[idx], &board.hand.cards[*card].name),
+            GameEvent::CardBought(zone, card) => {
+                let name = &board.stores.iter()
+                    .find(|s| s.menu.zone == *zone)
+                    .expect("Buy in unknown store")
+                    .menu.cards[*card].name;
+                count(&mut record.bought[idx], name)
+            },
+            _ => {}
+        }
+
+        let given = control.get_board_mut(idx).apply_action(&action);
+        if let Some(mut card) = given {
+            card.owner = Some(idx);
+            control.get_board_mut(opponent_idx).receive(card);
+        }
+
+        if let GameEvent::EndTurn = action {
+            return;
+        }
+    }
+
+    control.get_board_mut(idx).apply_action(&GameEvent::EndTurn);
+}
+
+fn count(counts: &mut HashMap<String, u32>, name: &str) {
+    *counts.entry(name.to_string()).or_insert(0) += 1;
+}
+
+#[derive(Debug, Default)]
+struct PlayerStats {
+    games: u32,
+    wins: u32,
+}
+
+#[derive(Debug, Default)]
+struct CardStats {
+    bought: u32,
+    played: u32,
+    owners: u32, // players who bought it in a game
+    owner_wins: u32, // those of them who won
+}
+
+/// Results of many games
+#[derive(Debug, Default)]
+pub struct Statistics {
+    games: u32,
+    draws: u32,
+    first_player_wins: u32,
+    rounds: u32,
+    turns: u32,
+    players: BTreeMap<String, PlayerStats>,
+    cards: BTreeMap<String, CardStats>,
+}
+
+impl Statistics {
+    pub fn add(&mut self, record: &GameRecord) {
+        self.games += 1;
+        self.rounds += record.rounds as u32;
+        self.turns += record.turns;
+        match record.winner {
+            None => self.draws += 1,
+            Some(0) => self.first_player_wins += 1,
+            _ => {}
+        }
+
+        for (seat, name) in record.players.iter().enumerate() {
+            let won = record.winner == Some(seat);
+            let player = self.players.entry(name.clone()).or_default();
+            player.games += 1;
+            if won {
+                player.wins += 1;
+            }
+
+            for (card, times) in record.bought[seat].iter() {
+                let stats = self.cards.entry(card.clone()).or_default();
+                stats.bought += times;
+                stats.owners += 1;
+                if won {
+                    stats.owner_wins += 1;
+                }
+            }
+            for (card, times) in record.played[seat].iter() {
+                self.cards.entry(card.clone()).or_default().played += times;
+            }
+        }
+    }
+}
+
+fn percent(part: u32, whole: u32) -> f32 {
+    if whole == 0 { 0.0 } else { 100.0 * part as f32 / whole as f32 }
+}
+
+impl fmt::Display for Statistics {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let games = self.games.max(1) as f32;
+        writeln!(f, "Games: {}, draws: {}", self.games, self.draws)?;
+        writeln!(f, "Average length: {:.1} rounds, {:.1} turns", self.rounds as f32 / games, self.turns as f32 / games)?;
+        writeln!(f, "First player won {:.1}% of decided games", percent(self.first_player_wins, self.games - self.draws))?;
+
+        writeln!(f)?;
+        for (name, player) in self.players.iter() {
+            writeln!(f, "{}: won {} of {} ({:.1}%)", name, player.wins, player.games, percent(player.wins, player.games))?;
+        }
+
+        // win rate of an average player, to compare the cards with
+        let wins: u32 = self.players.values().map(|p| p.wins).sum();
+        let seats: u32 = self.players.values().map(|p| p.games).sum();
+        writeln!(f)?;
+        writeln!(f, "{:<24} {:>8} {:>8} {:>10} {:>12}", "Card", "bought", "played", "uses/game", "owners won")?;
+        let mut cards: Vec<(&String, &CardStats)> = self.cards.iter().collect();
+        cards.sort_by_key(|(_, c)| std::cmp::Reverse(c.bought + c.played));
+        for (name, card) in cards {
+            let owners_won = if card.owners > 0 {
+                format!("{:.1}%", percent(card.owner_wins, card.owners))
+            } else {
+                "-".to_string()
+            };
+            writeln!(f, "{:<24} {:>8} {:>8} {:>10.2} {:>12}", name, card.bought, card.played,
+                (card.bought + card.played) as f32 / games, owners_won)?;
+        }
+        writeln!(f, "(players won {:.1}% of their games on average)", percent(wins, seats))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game_control::Player;
+    use crate::game_objects::fixtures::*;
+
+    fn seat(name: &str, opponent_idx: usize) -> BoardState {
+        BoardState {
+            player: Player{ name: name.to_string(), opponent_idx, ..Default::default() },
+            deck: Box::new(deck_of(&["a", "b", "c", "d", "e", "f"])),
+            ..board(Vec::new(), vec!(named("house")), Vec::new())
+        }
+    }
+
+    #[test]
+    fn game_is_played_until_the_rounds_run_out() {
+        let victory = VictoryRules{ points: None, rounds: Some(3) };
+        let record = play_game(vec!(seat("first", 1), seat("second", 0)), victory, Deck::default());
+
+        assert_eq!(record.players, ["first", "second"]);
+        assert_eq!(record.rounds, 3);
+        assert_eq!(record.turns, 6);
+        assert_eq!(record.winner, None);
+    }
+}
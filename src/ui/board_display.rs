@@ -6,14 +6,18 @@ use quicksilver::prelude::*;
 use quicksilver::lifecycle::{Event, Window};
 use serde::export::fmt::Debug;
 use std::collections::HashMap;
+use std::iter;
 
 use super::widgets::*;
 use crate::game_objects::{BoardZone, Globals}; //, GameData, Card, Effect, };
+use crate::loading::Assets;
 
 // pub const WINDOW_SIZE_W: f32 = 1280.0;
 // pub const WINDOW_SIZE_H: f32 = 800.0;
 const PLAYER_BOARD_FROM_TOP: f32 = 300.0;
 const BASE_Z_INDEX: f32 = 1.0; // widgets will be layered starting with this Z
+const HIGHLIGHT_Z_INDEX: f32 = 5.0; // above zones, beneath the cards in them
+const EXPLANATION_LINES: usize = 6; // latest decisions of the opponent shown
 
 #[derive(Debug)]
 pub struct BoardDisplay {
@@ -24,12 +28,21 @@ pub struct BoardDisplay {
 
 impl BoardDisplay {
     pub fn new(gameplay_state: &GameplayState, handlers: HashMap<BoardZone, CardHandler>, window_w: f32, window_h: f32) -> Box<Self> {
-        let assets = gameplay_state.get_assets();
+        Self::for_boards(gameplay_state.get_board(),
+                         gameplay_state.get_opponent(),
+                         gameplay_state.get_assets(),
+                         handlers,
+                         window_w,
+                         window_h)
+    }
+
+    /// Display the board as seen by its player. Used to show the board of the player that is not on turn.
+    pub fn for_boards(board: &BoardState, opponent: &BoardState, assets: &Assets, handlers: HashMap<BoardZone, CardHandler>, window_w: f32, window_h: f32) -> Box<Self> {
         let mut widgets = Vec::new();
 
         // Nametag
         widgets.push(Box::new(Button::new(
-            format!("Me: {}", gameplay_state.get_board().player.name),
+            format!("Me: {}", board.player.name),
             Vector::new(UI_UNIT * 5.0, UI_UNIT * 5.0),
             BASE_Z_INDEX,
             &assets,
@@ -37,7 +50,7 @@ impl BoardDisplay {
         ),
         ) as Box<dyn Widget>);
         widgets.push(Box::new(Button::new(
-            format!("Foe: {}", gameplay_state.get_opponent().player.name),
+            format!("Foe: {}", opponent.player.name),
             Vector::new(UI_UNIT * 5.0, UI_UNIT * 10.0),
             BASE_Z_INDEX,
             &assets,
@@ -47,7 +60,7 @@ impl BoardDisplay {
 
         // Next turn button
         widgets.push(Box::new(Button::new(
-            format!("End turn\ndeck: {}", gameplay_state.get_board().deck.len()),
+            format!("End turn\ndeck: {}", board.deck.len()),
             Vector::new(UI_UNIT * 7.0, UI_UNIT * 45.0),
             BASE_Z_INDEX,
             &assets,
@@ -56,7 +69,7 @@ impl BoardDisplay {
         ) as Box<dyn Widget>);
 
         // Hand
-        let hand_zone = CardZone::<CardFull>::from_container(&gameplay_state.get_board().hand,
+        let hand_zone = CardZone::<CardFull>::from_container(&board.hand,
                                                              Vector::new(13.0 * UI_UNIT, 35.0 * UI_UNIT),
                                                              ZoneDirection::Horizontal,
                                                              BASE_Z_INDEX,
@@ -68,7 +81,7 @@ impl BoardDisplay {
 
         // Stores
         let base_store_position = Vector::new(UI_UNIT, PLAYER_BOARD_FROM_TOP);
-        for (num, store) in gameplay_state.get_board().stores.iter().enumerate() {
+        for (num, store) in board.stores.iter().enumerate() {
             let shop_zone = CardZone::<CardIcon>::from_container(&store.menu,
                                                                  base_store_position + Vector::new(0, UI_UNIT * 4.0 * num as f32), // 4U widget height + 1U padding + 1U gap
                                                                  ZoneDirection::Horizontal,
@@ -81,7 +94,7 @@ impl BoardDisplay {
         // buildings
         let base_playzone_position = Vector::new(60.0 * UI_UNIT, PLAYER_BOARD_FROM_TOP);
 
-        let build_zone = CardZone::<CardIcon>::from_container(&gameplay_state.get_board().buildings,
+        let build_zone = CardZone::<CardIcon>::from_container(&board.buildings,
                                                               base_playzone_position,
                                                               ZoneDirection::Vertical,
                                                               BASE_Z_INDEX,
@@ -91,7 +104,7 @@ impl BoardDisplay {
 
         // kaiju_zone
         let kaiju_position = base_playzone_position + Vector::new(UI_UNIT * 9.0, 0); // 7U widget height + 1U padding + 1U gap
-        let kaiju_zone = CardZone::<CardIcon>::from_container(&gameplay_state.get_board().kaiju_zone,
+        let kaiju_zone = CardZone::<CardIcon>::from_container(&board.kaiju_zone,
                                                               kaiju_position,
                                                               ZoneDirection::Vertical,
                                                               BASE_Z_INDEX,
@@ -99,10 +112,10 @@ impl BoardDisplay {
                                                               &handlers);
         widgets.push(Box::new(kaiju_zone));
 
-        let base_numbers_position = Vector::new(4.0 * UI_UNIT, PLAYER_BOARD_FROM_TOP + 12.0 * UI_UNIT);
+        let base_numbers_position = Vector::new(4.0 * UI_UNIT, PLAYER_BOARD_FROM_TOP + 13.0 * UI_UNIT);
 
         for (num, currency) in Globals::in_game().iter().enumerate() {
-            let value = gameplay_state.get_board().globals.get(*currency);
+            let value = board.globals.get(*currency);
             widgets.push(Box::new(Button::new(
                 format!("{:?}\n {}", currency, value),
                 base_numbers_position + Vector::new(UI_UNIT * 5.0, 0) * num as f32,
@@ -112,6 +125,51 @@ impl BoardDisplay {
             )));
         }
 
+        let points_position = base_numbers_position + Vector::new(UI_UNIT * 5.0, 0) * Globals::in_game().len() as f32;
+        widgets.push(Box::new(Button::new(
+            format!("Points\n {}", board.score()),
+            points_position,
+            BASE_Z_INDEX,
+            &assets,
+            None,
+        )));
+
+        // log of recent happenings
+        let base_log_position = Vector::new(40.0 * UI_UNIT, UI_UNIT);
+        for (num, notice) in board.log.iter().enumerate() {
+            widgets.push(Box::new(Label::new(
+                format!("{}", notice),
+                base_log_position + Vector::new(0, UI_UNIT * 2.0 * num as f32),
+                BASE_Z_INDEX,
+                &assets,
+            )));
+        }
+
+        if let Some(event) = &board.event {
+            widgets.push(Box::new(Label::new(
+                format!("Event: {}", event.name),
+                base_log_position + Vector::new(0, UI_UNIT * 12.0),
+                BASE_Z_INDEX,
+                &assets,
+            )));
+        }
+
+        // what the AI opponent did in its last turn, and why
+        if !opponent.explained.is_empty() {
+            let base_panel_position = Vector::new(33.0 * UI_UNIT, PLAYER_BOARD_FROM_TOP + UI_UNIT);
+            let shown = opponent.explained.len().min(EXPLANATION_LINES);
+            let lines = iter::once(format!("{} thinks:", opponent.player.name))
+                .chain(opponent.explained[opponent.explained.len() - shown..].iter().cloned());
+            for (num, line) in lines.enumerate() {
+                widgets.push(Box::new(Label::new(
+                    line,
+                    base_panel_position + Vector::new(0, UI_UNIT * 2.0 * num as f32),
+                    BASE_Z_INDEX,
+                    &assets,
+                )));
+            }
+        }
+
         Box::new(Self {
             widgets,
             window_w,
@@ -119,6 +177,34 @@ impl BoardDisplay {
         })
     }
 
+    /// Add a widget specific to the current state (prompts, extra buttons...)
+    pub fn add_widget(&mut self, widget: Box<dyn Widget>) {
+        self.widgets.push(widget);
+    }
+
+    /// Make the card stand out, e.g. when the AI is about to play it. Cards that aren't shown stay as they are.
+    pub fn highlight(&mut self, zone: BoardZone, idx: usize) {
+        if let Some(area) = self.widgets.iter().find_map(|w| w.card_area(zone, idx)) {
+            self.widgets.push(Box::new(Highlight::new(area, HIGHLIGHT_Z_INDEX)));
+        }
+    }
+
+    /// Highlight the cards the action is about
+    pub fn highlight_action(&mut self, action: &GameEvent) {
+        match *action {
+            GameEvent::CardPicked(idx) => self.highlight(BoardZone::Hand, idx),
+            GameEvent::CardTargeted(_, idx, target_zone, target) => {
+                self.highlight(BoardZone::Hand, idx);
+                if target_zone != BoardZone::None {
+                    self.highlight(target_zone, target);
+                }
+            },
+            GameEvent::CardBought(zone, idx) => self.highlight(zone, idx),
+            GameEvent::CardActivated(idx) => self.highlight(BoardZone::Buildings, idx),
+            _ => {}
+        }
+    }
+
     pub fn handle_io(&mut self, event: Event) -> Option<GameEvent> {
         match event {
             // TODO: generalize to arbitrary window sizes
@@ -11,11 +11,17 @@ use super::widgets::*;
 use crate::game_objects::{GameData, Globals, Card, Effect, BoardZone};
 use crate::game_logic::BoardState;
 use crate::loading::Assets;
+use crate::ai::AI;
+use crate::analysis::{RecordedDecision, Analysis};
+use std::time::{Duration, Instant};
 
 pub const WINDOW_SIZE_W: f32 = 1280.0;
 pub const WINDOW_SIZE_H: f32 = 800.0;
 const PLAYER_BOARD_FROM_TOP: f32 = 300.0;
 const BASE_Z_INDEX: f32 = 1.0; // widgets will be layered starting with this Z
+const MISTAKES_SHOWN: usize = 5; // for each player that didn't win
+const ANALYSIS_MILLIS: u64 = 2000; // for all players together
+const ANALYSIS_FRAME_MILLIS: u64 = 30; // spent analysing in each update, so that the window keeps redrawing
 
 // TODO: cache widgets?
 #[derive(Derivative)]
@@ -23,22 +29,26 @@ const BASE_Z_INDEX: f32 = 1.0; // widgets will be layered starting with this Z
 pub struct GameEndState {
     boards: Vec<BoardState>,
     widgets: Vec<Box<dyn Widget>>,
+    analysis: Option<Analysis>, // until it is over
+    analysis_widgets: Vec<Box<dyn Widget>>, // a note that it is running, then its results
+    assets: Assets,
 }
 
 // TODO: load fonts in LoadingState
 impl GameEndState {
-    pub fn new(boards: Vec<BoardState>, assets: Assets) -> Box<Self> {
+    pub fn new(boards: Vec<BoardState>, assets: Assets, decisions: Vec<RecordedDecision>, analyst: Option<Box<AI>>) -> Box<Self> {
         let mut widgets = Vec::new();
 
         // Exit game
-        let loser = boards.iter()
-            .find(|b| b.is_defeated())
-            .expect("Someone had to lose")
-            .player
-            .clone();
+        let won = winner(&boards);
+        let result = match won {
+            Some(idx) if boards.iter().any(|b| b.is_defeated()) => format!("It is over, {} won", boards[idx].player.name),
+            Some(idx) => format!("It is over, {} won on points", boards[idx].player.name),
+            None => "It is over, it is a draw".to_string()
+        };
 
         widgets.push(Box::new(Button::new(
-            format!("It is over, {} lost", loser.name),
+            result,
             Vector::new(UI_UNIT * 5.0, UI_UNIT * 5.0),
             BASE_Z_INDEX,
             &assets,
@@ -47,10 +57,67 @@ impl GameEndState {
         ) as Box<dyn Widget>);
         println!("It is over");
 
-        Box::new(Self {
+        for (num, board) in boards.iter().enumerate() {
+            widgets.push(Box::new(Label::new(
+                format!("{}: {} points", board.player.name, board.score()),
+                Vector::new(UI_UNIT * 5.0, UI_UNIT * (10.0 + 2.0 * num as f32)),
+                BASE_Z_INDEX,
+                &assets,
+            )));
+        }
+
+        // what those who didn't win could have done better, found while the result is shown
+        let losers: Vec<usize> = (0..boards.len()).filter(|idx| Some(*idx) != won).collect();
+        let analysis = analyst.filter(|_| !losers.is_empty()).map(|analyst| {
+            println!("Analysing decisions with {:?}", analyst);
+            let deadline = Instant::now() + Duration::from_millis(ANALYSIS_MILLIS);
+            Analysis::new(decisions, losers, analyst, deadline)
+        });
+
+        let mut state = Self {
             boards,
             widgets,
-        })
+            analysis,
+            analysis_widgets: Vec::new(),
+            assets,
+        };
+        if state.analysis.is_some() {
+            state.show_analysis(vec!("Looking for mistakes...".to_string()));
+        }
+        Box::new(state)
+    }
+
+    fn analysis_over(&mut self, analysis: Analysis) {
+        let mut lines = Vec::new();
+        for idx in analysis.players() {
+            let name = &self.boards[*idx].player.name;
+            let mistakes = analysis.mistakes(*idx);
+            println!("Mistakes of {}:", name);
+            for mistake in mistakes.iter() {
+                println!("  {}", mistake);
+            }
+
+            if mistakes.is_empty() {
+                lines.push(format!("{} made no mistakes", name));
+            } else {
+                lines.push(format!("Biggest mistakes of {}:", name));
+                lines.extend(mistakes.iter().take(MISTAKES_SHOWN).map(|m| format!("  {}", m)));
+            }
+        }
+        self.show_analysis(lines);
+    }
+
+    fn show_analysis(&mut self, lines: Vec<String>) {
+        let base_analysis_position = Vector::new(UI_UNIT * 5.0, UI_UNIT * (12.0 + 2.0 * self.boards.len() as f32));
+        self.analysis_widgets = lines.into_iter()
+            .enumerate()
+            .map(|(num, line)| Box::new(Label::new(
+                line,
+                base_analysis_position + Vector::new(0, UI_UNIT * 2.0 * num as f32),
+                BASE_Z_INDEX,
+                &self.assets,
+            )) as Box<dyn Widget>)
+            .collect();
     }
 }
 
@@ -60,6 +127,9 @@ impl Default for GameEndState {
         Self {
             boards: Vec::new(),
             widgets: Vec::new(),
+            analysis: None,
+            analysis_widgets: Vec::new(),
+            assets: Assets::default(),
         }
     }
 }
@@ -85,6 +155,11 @@ impl AutomatonState for GameEndState {
     }
 
     fn update(&mut self) -> Box<dyn AutomatonState> {
+        let frame_end = Instant::now() + Duration::from_millis(ANALYSIS_FRAME_MILLIS);
+        if self.analysis.as_mut().is_some_and(|analysis| analysis.advance(frame_end)) {
+            let analysis = self.analysis.take().expect("Analysis that is over");
+            self.analysis_over(analysis);
+        }
         Box::new(take(self))
     }
 
@@ -92,7 +167,7 @@ impl AutomatonState for GameEndState {
     fn draw(&self, window: &mut Window) -> () {
         // TODO Draw result
 
-        for widget in &self.widgets {
+        for widget in self.widgets.iter().chain(self.analysis_widgets.iter()) {
             widget.draw(window).unwrap();
         }
     }
@@ -6,17 +6,20 @@ use std::collections::HashMap;
 use quicksilver::prelude::*;
 use derivative::*;
 use std::mem::take;
+use std::time::{Duration, Instant};
 
 mod widgets;
 mod board_display;
 pub mod game_end_state;
 
 use widgets::*;
-use crate::game_objects::{GameData, Globals, Card, Effect, BoardZone};
+use crate::game_objects::{GameData, Globals, Card, Effect, BoardZone, StackItem, Mode, ExtraCost};
 use board_display::BoardDisplay;
+use crate::ai::{AiPacing, describe_action};
 
 pub const WINDOW_SIZE_W: f32 = 1280.0;
 pub const WINDOW_SIZE_H: f32 = 800.0;
+const ANNOUNCEMENT_POSITION: Vector = Vector{ x: UI_UNIT * 3.0, y: UI_UNIT * 14.0 }; // what the AI does or suggests
 
 // TODO: cache widgets?
 #[derive(Derivative)]
@@ -24,6 +27,7 @@ pub const WINDOW_SIZE_H: f32 = 800.0;
 pub struct TakeTurnState {
     gameplay_state: Box<GameplayState>,
     display: Box<BoardDisplay>,
+    hinted: bool, // the hint is shown already
 }
 
 // TODO: load fonts in LoadingState
@@ -33,18 +37,52 @@ impl TakeTurnState {
         let mut handler_dict = HashMap::<BoardZone, CardHandler>::new();
 
         handler_dict.insert(BoardZone::Hand, Box::new(|idx, _card, _zone| Some(GameEvent::CardPicked(idx))));
+        handler_dict.insert(BoardZone::Buildings, Box::new(|idx, card, _zone| card.activated.as_ref().map(|_| GameEvent::CardActivated(idx))));
 
         for (_, store) in gameplay_state.get_board().stores.iter().enumerate() {
             handler_dict.insert(store.menu.zone, Box::new(|idx, _card, zone| Some(GameEvent::CardBought(zone, idx))));
         }
 
-        let display = BoardDisplay::new(&gameplay_state, handler_dict, WINDOW_SIZE_W, WINDOW_SIZE_H);
+        let mut display = BoardDisplay::new(&gameplay_state, handler_dict, WINDOW_SIZE_W, WINDOW_SIZE_H);
+        display.add_widget(Box::new(Button::new(
+            "Hint".to_string(),
+            Vector::new(UI_UNIT * 7.0, UI_UNIT * 40.0),
+            1.0,
+            gameplay_state.get_assets(),
+            Some(GameEvent::HintRequested),
+        )));
 
         Box::new(Self {
             gameplay_state,
             display,
+            hinted: false,
         })
     }
+
+    /// Shows what the player's AI would do now, and why
+    fn show_hint(&mut self) {
+        if self.hinted {
+            return;
+        }
+        self.hinted = true;
+
+        let hint = match self.gameplay_state.hint() {
+            Some(hint) => hint,
+            None => return
+        };
+
+        let board = self.gameplay_state.get_board();
+        let text = format!("Hint for {}: {}", board.player.name, hint.describe(board));
+        println!("{}", text);
+
+        self.display.highlight_action(&hint.action);
+        self.display.add_widget(Box::new(Label::new(
+            format!("Hint: {}", hint.summary(board)),
+            ANNOUNCEMENT_POSITION,
+            1.0,
+            self.gameplay_state.get_assets(),
+        )));
+    }
 }
 
 // This is only a placeholder, to allow us to take() ourselves from &mut Self
@@ -53,6 +91,7 @@ impl Default for TakeTurnState {
         Self {
             gameplay_state: Box::new(GameplayState::default()),
             display: Box::new(BoardDisplay::default()),
+            hinted: false,
         }
     }
 }
@@ -63,8 +102,16 @@ impl AutomatonState for TakeTurnState {
             GameEvent::IO(Event::Key(Key::Escape, ButtonState::Released)) => {
                 Box::new(GameEndedState {})
             }
+            GameEvent::IO(Event::Key(Key::H, ButtonState::Released)) => {
+                self.show_hint();
+                Box::new(take(self))
+            }
             GameEvent::IO(io) => {
                 match self.display.handle_io(io) {
+                    Some(GameEvent::HintRequested) => {
+                        self.show_hint();
+                        Box::new(take(self))
+                    }
                     Some(event) => self.gameplay_state.event(event),
                     None => Box::new(take(self))
                 }
@@ -84,6 +131,99 @@ impl AutomatonState for TakeTurnState {
     }
 }
 
+/// Shows what the AI on turn is about to do, highlighting the cards involved, and does it when its time comes.
+/// Lets the humans watch the AI play. Space hurries the AI along, + and - change its pace.
+#[derive(Derivative)]
+#[derivative(Debug)]
+pub struct AiTurnState {
+    gameplay_state: Box<GameplayState>,
+    display: Box<BoardDisplay>,
+    action: Option<GameEvent>,
+    shown: Instant,
+}
+
+impl AiTurnState {
+    pub fn new(gameplay_state: Box<GameplayState>, action: GameEvent) -> Box<Self> {
+        let mut display = BoardDisplay::new(&gameplay_state, HashMap::new(), WINDOW_SIZE_W, WINDOW_SIZE_H);
+
+        let board = gameplay_state.get_board();
+        display.highlight_action(&action);
+        display.add_widget(Box::new(Label::new(
+            format!("{} will {}", board.player.name, describe_action(board, &action)),
+            ANNOUNCEMENT_POSITION,
+            1.0,
+            gameplay_state.get_assets(),
+        )));
+
+        Box::new(Self {
+            gameplay_state,
+            display,
+            action: Some(action),
+            shown: Instant::now(),
+        })
+    }
+
+    fn pacing(&mut self) -> &mut AiPacing {
+        self.gameplay_state.pacing_mut()
+    }
+
+    fn act(&mut self) -> Box<dyn AutomatonState> {
+        let action = self.action.take().expect("AI acted twice");
+        self.gameplay_state.event(action)
+    }
+}
+
+// This is only a placeholder, to allow us to take() ourselves from &mut Self
+impl Default for AiTurnState {
+    fn default() -> Self {
+        Self {
+            gameplay_state: Box::new(GameplayState::default()),
+            display: Box::new(BoardDisplay::default()),
+            action: None,
+            shown: Instant::now(),
+        }
+    }
+}
+
+impl AutomatonState for AiTurnState {
+    fn event(&mut self, event: GameEvent) -> Box<dyn AutomatonState> {
+        match event {
+            GameEvent::IO(Event::Key(Key::Escape, ButtonState::Released)) => {
+                Box::new(GameEndedState {})
+            }
+            GameEvent::IO(Event::Key(Key::Space, ButtonState::Released)) => self.act(),
+            GameEvent::IO(Event::Key(Key::Add, ButtonState::Released)) |
+            GameEvent::IO(Event::Key(Key::Equals, ButtonState::Released)) => {
+                self.pacing().faster();
+                Box::new(take(self))
+            }
+            GameEvent::IO(Event::Key(Key::Subtract, ButtonState::Released)) |
+            GameEvent::IO(Event::Key(Key::Minus, ButtonState::Released)) => {
+                self.pacing().slower();
+                Box::new(take(self))
+            }
+            GameEvent::IO(io) => {
+                self.display.handle_io(io);
+                Box::new(take(self))
+            }
+            _ => Box::new(take(self))
+        }
+    }
+
+    fn update(&mut self) -> Box<dyn AutomatonState> {
+        self.display.update();
+        if self.shown.elapsed() >= Duration::from_millis(self.pacing().millis) {
+            self.act()
+        } else {
+            Box::new(take(self))
+        }
+    }
+
+    fn draw(&self, window: &mut Window) -> () {
+        self.display.draw(window)
+    }
+}
+
 #[derive(Derivative)]
 #[derivative(Debug)]
 pub struct TargetingState {
@@ -102,7 +242,7 @@ impl TargetingState {
     pub fn new(gameplay_state: Box<GameplayState>, acting_card_source: BoardZone, acting_card_idx: usize, target_zone: BoardZone) -> Box<Self> {
 
         let mut handler_dict = HashMap::<BoardZone, CardHandler>::new();
-        handler_dict.insert(target_zone, Box::new(move  |idx, card, zone| Some(GameEvent::CardTargeted(acting_card_source, acting_card_idx, zone, idx))));
+        handler_dict.insert(target_zone, Box::new(move  |idx, _card, zone| Some(GameEvent::CardTargeted(acting_card_source, acting_card_idx, zone, idx))));
     
         let display = BoardDisplay::new(&gameplay_state, handler_dict, WINDOW_SIZE_W, WINDOW_SIZE_H);
     
@@ -178,4 +318,518 @@ impl AutomatonState for TargetingState {
     fn draw(&self, window: &mut Window) -> () {
         self.display.draw(window)
     }
-}
\ No newline at end of file
+}
+/// Lets the player react with a card from their hand to something happening during a turn (possibly
+/// other player's). Shows the board of the reacting player.
+#[derive(Derivative)]
+#[derivative(Debug)]
+pub struct ReactionState {
+    gameplay_state: Box<GameplayState>,
+    display: Box<BoardDisplay>,
+    reactor: usize,
+}
+
+impl ReactionState {
+    pub fn new(gameplay_state: Box<GameplayState>, reactor: usize, item: StackItem) -> Box<Self> {
+        let (board, opponent) = if gameplay_state.is_on_turn(reactor) {
+            (gameplay_state.get_board(), gameplay_state.get_opponent())
+        } else {
+            (gameplay_state.get_opponent(), gameplay_state.get_board())
+        };
+
+        let mut handler_dict = HashMap::<BoardZone, CardHandler>::new();
+        let trigger = item.clone();
+        handler_dict.insert(BoardZone::Hand, Box::new(move |idx, card, _zone| match &card.reaction {
+            Some(reaction) if reaction.matches(&trigger) => Some(GameEvent::Reacted(reactor, Some(idx))),
+            _ => None
+        }));
+
+        let mut display = BoardDisplay::for_boards(board, opponent, gameplay_state.get_assets(), handler_dict, WINDOW_SIZE_W, WINDOW_SIZE_H);
+        display.add_widget(Box::new(Button::new(
+            format!("{}, react to\n{}?", board.player.name, item.source.name),
+            Vector::new(UI_UNIT * 25.0, UI_UNIT * 5.0),
+            1.0,
+            gameplay_state.get_assets(),
+            None,
+        )));
+        display.add_widget(Box::new(Button::new(
+            "Pass".to_string(),
+            Vector::new(UI_UNIT * 25.0, UI_UNIT * 10.0),
+            1.0,
+            gameplay_state.get_assets(),
+            Some(GameEvent::Reacted(reactor, None)),
+        )));
+
+        Box::new(Self {
+            gameplay_state,
+            display,
+            reactor,
+        })
+    }
+}
+
+// This is only a placeholder, to allow us to take() ourselves from &mut Self
+impl Default for ReactionState {
+    fn default() -> Self {
+        Self {
+            gameplay_state: Box::new(GameplayState::default()),
+            display: Box::new(BoardDisplay::default()),
+            reactor: 0,
+        }
+    }
+}
+
+impl AutomatonState for ReactionState {
+    fn event(&mut self, event: GameEvent) -> Box<dyn AutomatonState> {
+        match event {
+            GameEvent::IO(Event::Key(Key::Escape, ButtonState::Released)) => {
+                Box::new(GameEndedState {})
+            }
+            GameEvent::IO(Event::MouseButton(MouseButton::Right, ButtonState::Released)) => {
+                // Pass
+                self.gameplay_state.event(GameEvent::Reacted(self.reactor, None))
+            }
+            GameEvent::IO(io) => {
+                match self.display.handle_io(io) {
+                    // only reactions are allowed (the display would also let us end the turn etc.)
+                    Some(event @ GameEvent::Reacted(_, _)) => self.gameplay_state.event(event),
+                    _ => Box::new(take(self))
+                }
+            }
+            _ => Box::new(take(self))
+        }
+    }
+
+    fn update(&mut self) -> Box<dyn AutomatonState> {
+        self.display.update();
+        Box::new(take(self))
+    }
+
+    fn draw(&self, window: &mut Window) -> () {
+        self.display.draw(window)
+    }
+}
+
+/// Asks a player a question over their board. Only the offered options can be clicked; each option
+/// sends its event to the gameplay state.
+#[derive(Derivative)]
+#[derivative(Debug)]
+pub struct PromptState {
+    gameplay_state: Box<GameplayState>,
+    display: Box<BoardDisplay>,
+    options: Vec<Button>,
+}
+
+impl PromptState {
+    pub fn new(gameplay_state: Box<GameplayState>, viewer: usize, question: String, options: Vec<(String, GameEvent)>) -> Box<Self> {
+        let (board, opponent) = if gameplay_state.is_on_turn(viewer) {
+            (gameplay_state.get_board(), gameplay_state.get_opponent())
+        } else {
+            (gameplay_state.get_opponent(), gameplay_state.get_board())
+        };
+
+        let mut display = BoardDisplay::for_boards(board, opponent, gameplay_state.get_assets(), HashMap::new(), WINDOW_SIZE_W, WINDOW_SIZE_H);
+        display.add_widget(Box::new(Button::new(
+            question,
+            Vector::new(UI_UNIT * 25.0, UI_UNIT * 5.0),
+            1.0,
+            gameplay_state.get_assets(),
+            None,
+        )));
+
+        let options = options.into_iter()
+            .enumerate()
+            .map(|(num, (text, event))| Button::new(
+                text,
+                Vector::new(UI_UNIT * (20.0 + 10.0 * num as f32), UI_UNIT * 11.0),
+                1.0,
+                gameplay_state.get_assets(),
+                Some(event)))
+            .collect();
+
+        Box::new(Self {
+            gameplay_state,
+            display,
+            options,
+        })
+    }
+}
+
+// This is only a placeholder, to allow us to take() ourselves from &mut Self
+impl Default for PromptState {
+    fn default() -> Self {
+        Self {
+            gameplay_state: Box::new(GameplayState::default()),
+            display: Box::new(BoardDisplay::default()),
+            options: Vec::new(),
+        }
+    }
+}
+
+impl AutomatonState for PromptState {
+    fn event(&mut self, event: GameEvent) -> Box<dyn AutomatonState> {
+        match event {
+            GameEvent::IO(Event::Key(Key::Escape, ButtonState::Released)) => {
+                Box::new(GameEndedState {})
+            }
+            GameEvent::IO(Event::MouseMoved(position)) => {
+                self.display.handle_io(Event::MouseMoved(position));
+                for option in self.options.iter_mut() {
+                    option.update_hovered(position);
+                }
+                Box::new(take(self))
+            }
+            GameEvent::IO(Event::MouseButton(MouseButton::Left, ButtonState::Released)) => {
+                match self.options.iter().find_map(|o| o.maybe_activate()) {
+                    Some(event) => self.gameplay_state.event(event),
+                    None => Box::new(take(self))
+                }
+            }
+            _ => Box::new(take(self))
+        }
+    }
+
+    fn update(&mut self) -> Box<dyn AutomatonState> {
+        self.display.update();
+        Box::new(take(self))
+    }
+
+    fn draw(&self, window: &mut Window) -> () {
+        self.display.draw(window);
+        for option in self.options.iter() {
+            option.draw(window).unwrap();
+        }
+    }
+}
+
+/// Shows the top cards of a deck above the board of the player on turn. If the cards can be rearranged, clicking a card
+/// moves it between the top and the bottom of the deck; cards returned to the top go beneath those already there.
+#[derive(Derivative)]
+#[derivative(Debug)]
+pub struct ArrangeState {
+    gameplay_state: Box<GameplayState>,
+    display: Box<BoardDisplay>,
+    cards: Vec<Card>,
+    rearrange: bool,
+    top: Vec<usize>, // indices into cards, topmost first
+    bottom: Vec<usize>,
+    widgets: Vec<Box<dyn Widget>>,
+}
+
+const ARRANGE_Z_INDEX: f32 = 50.0; // above the board
+
+impl ArrangeState {
+    pub fn new(gameplay_state: Box<GameplayState>, cards: Vec<Card>, rearrange: bool) -> Box<Self> {
+        let display = BoardDisplay::new(&gameplay_state, HashMap::new(), WINDOW_SIZE_W, WINDOW_SIZE_H);
+        let top = (0..cards.len()).collect();
+
+        let mut state = Self {
+            gameplay_state,
+            display,
+            cards,
+            rearrange,
+            top,
+            bottom: Vec::new(),
+            widgets: Vec::new(),
+        };
+        state.layout();
+
+        Box::new(state)
+    }
+
+    /// Recreates the widgets, showing each card where it currently is
+    fn layout(&mut self) {
+        let assets = self.gameplay_state.get_assets();
+        let action = |idx: usize| if self.rearrange { Some(GameEvent::CardPicked(idx)) } else { None };
+
+        let mut top_zone = CardZone::<CardFull>::new(BoardZone::None,
+                                                     Vector::new(13.0 * UI_UNIT, UI_UNIT),
+                                                     ZoneDirection::Horizontal,
+                                                     ARRANGE_Z_INDEX);
+        for idx in self.top.iter() {
+            top_zone.add(self.cards[*idx].clone(), assets, action(*idx));
+        }
+
+        let mut bottom_zone = CardZone::<CardIcon>::new(BoardZone::None,
+                                                        Vector::new(13.0 * UI_UNIT, 15.0 * UI_UNIT),
+                                                        ZoneDirection::Horizontal,
+                                                        ARRANGE_Z_INDEX);
+        for idx in self.bottom.iter() {
+            bottom_zone.add(self.cards[*idx].clone(), assets, action(*idx));
+        }
+
+        let done = Button::new(
+            if self.rearrange { "Done".to_string() } else { "OK".to_string() },
+            Vector::new(UI_UNIT * 5.0, UI_UNIT * 15.0),
+            ARRANGE_Z_INDEX,
+            assets,
+            Some(GameEvent::Arranged(self.top.clone(), self.bottom.clone())));
+
+        self.widgets = vec!(Box::new(top_zone), Box::new(bottom_zone), Box::new(done));
+    }
+
+    /// Moves the card from the top to the bottom of the deck, or back
+    fn toggle(&mut self, idx: usize) {
+        if let Some(pos) = self.top.iter().position(|i| *i == idx) {
+            self.top.remove(pos);
+            self.bottom.push(idx);
+        } else if let Some(pos) = self.bottom.iter().position(|i| *i == idx) {
+            self.bottom.remove(pos);
+            self.top.push(idx);
+        }
+
+        self.layout();
+    }
+}
+
+// This is only a placeholder, to allow us to take() ourselves from &mut Self
+impl Default for ArrangeState {
+    fn default() -> Self {
+        Self {
+            gameplay_state: Box::new(GameplayState::default()),
+            display: Box::new(BoardDisplay::default()),
+            cards: Vec::new(),
+            rearrange: false,
+            top: Vec::new(),
+            bottom: Vec::new(),
+            widgets: Vec::new(),
+        }
+    }
+}
+
+impl AutomatonState for ArrangeState {
+    fn event(&mut self, event: GameEvent) -> Box<dyn AutomatonState> {
+        match event {
+            GameEvent::IO(Event::Key(Key::Escape, ButtonState::Released)) => {
+                Box::new(GameEndedState {})
+            }
+            GameEvent::IO(Event::MouseMoved(position)) => {
+                self.display.handle_io(Event::MouseMoved(position));
+                for widget in self.widgets.iter_mut() {
+                    widget.update_hovered(position);
+                }
+                Box::new(take(self))
+            }
+            GameEvent::IO(Event::MouseButton(MouseButton::Left, ButtonState::Released)) => {
+                match self.widgets.iter().find_map(|w| w.maybe_activate()) {
+                    Some(GameEvent::CardPicked(idx)) => {
+                        self.toggle(idx);
+                        Box::new(take(self))
+                    }
+                    Some(event) => self.gameplay_state.event(event),
+                    None => Box::new(take(self))
+                }
+            }
+            _ => Box::new(take(self))
+        }
+    }
+
+    fn update(&mut self) -> Box<dyn AutomatonState> {
+        self.display.update();
+        Box::new(take(self))
+    }
+
+    fn draw(&self, window: &mut Window) -> () {
+        self.display.draw(window);
+        for widget in self.widgets.iter() {
+            widget.draw(window).unwrap();
+        }
+    }
+}
+
+/// Lets the player on turn pick options of a modal effect. Clicking an option selects it (or deselects it again);
+/// once enough options are selected, they are sent to the gameplay state.
+#[derive(Derivative)]
+#[derivative(Debug)]
+pub struct ChoiceState {
+    gameplay_state: Box<GameplayState>,
+    display: Box<BoardDisplay>,
+    options: Vec<Mode>,
+    picks: usize,
+    selected: Vec<usize>,
+    buttons: Vec<Button>,
+}
+
+impl ChoiceState {
+    pub fn new(gameplay_state: Box<GameplayState>, options: Vec<Mode>, picks: usize) -> Box<Self> {
+        let mut display = BoardDisplay::new(&gameplay_state, HashMap::new(), WINDOW_SIZE_W, WINDOW_SIZE_H);
+        let question = match picks {
+            1 => "Choose one".to_string(),
+            _ => format!("Choose {}", picks),
+        };
+        display.add_widget(Box::new(Button::new(
+            question,
+            Vector::new(UI_UNIT * 25.0, UI_UNIT * 5.0),
+            1.0,
+            gameplay_state.get_assets(),
+            None,
+        )));
+
+        let mut state = Self {
+            gameplay_state,
+            display,
+            options,
+            picks,
+            selected: Vec::new(),
+            buttons: Vec::new(),
+        };
+        state.layout();
+
+        Box::new(state)
+    }
+
+    /// Recreates the option buttons, marking the selected ones
+    fn layout(&mut self) {
+        let assets = self.gameplay_state.get_assets();
+        let selected = &self.selected;
+
+        self.buttons = self.options.iter()
+            .enumerate()
+            .map(|(num, mode)| Button::new(
+                if selected.contains(&num) { format!("* {}", mode) } else { format!("{}", mode) },
+                Vector::new(UI_UNIT * (20.0 + 10.0 * num as f32), UI_UNIT * 11.0),
+                1.0,
+                assets,
+                Some(GameEvent::CardPicked(num))))
+            .collect();
+    }
+
+    fn toggle(&mut self, num: usize) -> Box<dyn AutomatonState> {
+        match self.selected.iter().position(|s| *s == num) {
+            Some(pos) => { self.selected.remove(pos); },
+            None => self.selected.push(num),
+        }
+
+        if self.selected.len() == self.picks {
+            let chosen = self.selected.clone();
+            return self.gameplay_state.event(GameEvent::Chosen(chosen));
+        }
+
+        self.layout();
+        Box::new(take(self))
+    }
+}
+
+// This is only a placeholder, to allow us to take() ourselves from &mut Self
+impl Default for ChoiceState {
+    fn default() -> Self {
+        Self {
+            gameplay_state: Box::new(GameplayState::default()),
+            display: Box::new(BoardDisplay::default()),
+            options: Vec::new(),
+            picks: 0,
+            selected: Vec::new(),
+            buttons: Vec::new(),
+        }
+    }
+}
+
+impl AutomatonState for ChoiceState {
+    fn event(&mut self, event: GameEvent) -> Box<dyn AutomatonState> {
+        match event {
+            GameEvent::IO(Event::Key(Key::Escape, ButtonState::Released)) => {
+                Box::new(GameEndedState {})
+            }
+            GameEvent::IO(Event::MouseMoved(position)) => {
+                self.display.handle_io(Event::MouseMoved(position));
+                for button in self.buttons.iter_mut() {
+                    button.update_hovered(position);
+                }
+                Box::new(take(self))
+            }
+            GameEvent::IO(Event::MouseButton(MouseButton::Left, ButtonState::Released)) => {
+                match self.buttons.iter().find_map(|b| b.maybe_activate()) {
+                    Some(GameEvent::CardPicked(num)) => self.toggle(num),
+                    _ => Box::new(take(self))
+                }
+            }
+            _ => Box::new(take(self))
+        }
+    }
+
+    fn update(&mut self) -> Box<dyn AutomatonState> {
+        self.display.update();
+        Box::new(take(self))
+    }
+
+    fn draw(&self, window: &mut Window) -> () {
+        self.display.draw(window);
+        for button in self.buttons.iter() {
+            button.draw(window).unwrap();
+        }
+    }
+}
+
+/// Lets the player choose the card to pay the extra cost of a played card with. Right click cancels playing the card.
+#[derive(Derivative)]
+#[derivative(Debug)]
+pub struct ExtraCostState {
+    gameplay_state: Box<GameplayState>,
+    display: Box<BoardDisplay>,
+}
+
+impl ExtraCostState {
+    pub fn new(gameplay_state: Box<GameplayState>, card_idx: usize, target: Option<(BoardZone, usize)>, extra_cost: ExtraCost, options: Vec<usize>) -> Box<Self> {
+        let mut handler_dict = HashMap::<BoardZone, CardHandler>::new();
+        handler_dict.insert(extra_cost.zone(), Box::new(move |idx, _card, _zone| match options.contains(&idx) {
+            true => Some(GameEvent::CostPaid(card_idx, target, idx)),
+            false => None
+        }));
+
+        let mut display = BoardDisplay::new(&gameplay_state, handler_dict, WINDOW_SIZE_W, WINDOW_SIZE_H);
+        display.add_widget(Box::new(Button::new(
+            format!("{}", extra_cost),
+            Vector::new(UI_UNIT * 25.0, UI_UNIT * 5.0),
+            1.0,
+            gameplay_state.get_assets(),
+            None,
+        )));
+
+        Box::new(Self {
+            gameplay_state,
+            display,
+        })
+    }
+}
+
+// This is only a placeholder, to allow us to take() ourselves from &mut Self
+impl Default for ExtraCostState {
+    fn default() -> Self {
+        Self {
+            gameplay_state: Box::new(GameplayState::default()),
+            display: Box::new(BoardDisplay::default()),
+        }
+    }
+}
+
+impl AutomatonState for ExtraCostState {
+    fn event(&mut self, event: GameEvent) -> Box<dyn AutomatonState> {
+        match event {
+            GameEvent::IO(Event::Key(Key::Escape, ButtonState::Released)) => {
+                Box::new(GameEndedState {})
+            }
+            GameEvent::IO(Event::MouseButton(MouseButton::Right, ButtonState::Released)) => {
+                // Cancel playing the card
+                let event = GameEvent::CardTargeted(BoardZone::None, 0, BoardZone::None, 0);
+                self.gameplay_state.event(event)
+            }
+            GameEvent::IO(io) => {
+                match self.display.handle_io(io) {
+                    // only paying is allowed (the display would also let us end the turn)
+                    Some(event @ GameEvent::CostPaid(_, _, _)) => self.gameplay_state.event(event),
+                    _ => Box::new(take(self))
+                }
+            }
+            _ => Box::new(take(self))
+        }
+    }
+
+    fn update(&mut self) -> Box<dyn AutomatonState> {
+        self.display.update();
+        Box::new(take(self))
+    }
+
+    fn draw(&self, window: &mut Window) -> () {
+        self.display.draw(window)
+    }
+}
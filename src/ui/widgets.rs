@@ -11,6 +11,7 @@ use std::rc::Rc;
 // should be even: we often use half of the unit (centering etc.) and half-pixels break the text antialiasing
 pub const UI_UNIT: f32 = 16.0;
 pub const TEXT_SIZE: f32 = UI_UNIT * 1.5;
+pub const MODIFIER_TEXT_SIZE: f32 = UI_UNIT;
 pub const PAD_SIZE: f32 = UI_UNIT;
 const TITLE_OFFSET: (f32, f32) = (5.0, 5.0); // card background image does not cover whole rectangle
 
@@ -23,6 +24,11 @@ pub trait Widget: std::fmt::Debug {
     fn maybe_activate(&self) -> Option<GameEvent>;
     fn draw(&self, window: &mut Window) -> Result<()>;
     fn update_hovered(&mut self, pointer_position: Vector);
+
+    /// Where the card with this index in the zone is drawn, if this widget shows it
+    fn card_area(&self, _zone: BoardZone, _idx: usize) -> Option<Rectangle> {
+        None
+    }
 }
 
 /// Implemented by widgets that represent a card.
@@ -146,6 +152,13 @@ impl<W: CardWidget> Widget for CardZone<W> {
             widget.update_hovered(pointer_position);
         }
     }
+
+    fn card_area(&self, zone: BoardZone, idx: usize) -> Option<Rectangle> {
+        if zone != self.zone_id {
+            return None;
+        }
+        self.widgets.get(idx).map(|w| w.bounding_box())
+    }
 }
 
 fn border_color(hovered: bool, available: bool, handled: bool) -> Color {
@@ -175,6 +188,7 @@ pub struct CardFull {
     image: Rc<Image>,
     background: Rc<Image>,
     title: Image,
+    modifiers: Option<Image>, // what auras do to this card
 }
 
 impl CardWidget for CardFull {
@@ -187,6 +201,15 @@ impl CardWidget for CardFull {
         ).expect("Can't render text");
         let image = assets.images[&card.image].clone();
         let background = assets.images[crate::loading::CARD_BACKGROUND_IMG].clone();
+        let modifiers = if card.modifiers.is_empty() {
+            None
+        } else {
+            let text = card.modifiers.iter().map(|m| format!("{}", m)).collect::<Vec<String>>().join("\n");
+            Some(assets.fonts[CARD_TITLE_FONT].render(
+                text.as_str(),
+                &FontStyle::new(MODIFIER_TEXT_SIZE, Color::YELLOW),
+            ).expect("Can't render text"))
+        };
         Self {
             card,
             area,
@@ -196,6 +219,7 @@ impl CardWidget for CardFull {
             image,
             background,
             title,
+            modifiers,
         }
     }
 }
@@ -229,6 +253,12 @@ impl Widget for CardFull {
         window.draw_ex(&self.area, Img(&self.background), Transform::IDENTITY, self.z_index + 1.0);
         window.draw_ex(&self.area, Img(&self.image), Transform::IDENTITY, self.z_index + 2.0);
         window.draw_ex(&text_rect, Img(&self.title), Transform::IDENTITY, self.z_index + 3.0);
+
+        if let Some(modifiers) = &self.modifiers {
+            let modifiers_position = position + Vector::new(0, self.area.size.y - modifiers.area().size.y);
+            let modifiers_rect = modifiers.area().translate(modifiers_position).translate(TITLE_OFFSET);
+            window.draw_ex(&modifiers_rect, Img(modifiers), Transform::IDENTITY, self.z_index + 3.0);
+        }
         Ok(())
     }
 
@@ -250,8 +280,13 @@ pub struct CardIcon {
 impl CardWidget for CardIcon {
     fn new(card: Card, top_left: Vector, z_index: f32, assets: &Assets, on_action: Option<GameEvent>) -> Self {
         let area = Rectangle::new(top_left, Vector::new(7.0 * UI_UNIT, 2.0 * UI_UNIT));
+        let modified_mark = if card.modifiers.is_empty() { "" } else { " *" };
+        let strikes = match card.escalation {
+            Some(_) if card.strikes > 0 => format!(" ({})", card.strikes),
+            _ => String::new()
+        };
         let image = assets.fonts[CARD_TITLE_FONT].render(
-            format!("{}", card.name).as_str(),
+            format!("{}{}{}", card.name, strikes, modified_mark).as_str(),
             &FontStyle::new(TEXT_SIZE, Color::WHITE),
         ).expect("Can't render text");
         Self {
@@ -364,4 +399,77 @@ impl Widget for Button {
     fn update_hovered(&mut self, pointer_position: Vector) {
         self.hovered = self.area.contains(pointer_position);
     }
-}
\ No newline at end of file
+}
+/// Plain line of text. Not interactive.
+#[derive(Debug)]
+pub struct Label {
+    area: Rectangle,
+    z_index: f32,
+    image: Image,
+}
+
+impl Label {
+    pub fn new(text: String, top_left: Vector, z_index: f32, assets: &Assets) -> Self {
+        let image = assets.fonts[CARD_TITLE_FONT].render(
+            text.as_str(),
+            &FontStyle::new(TEXT_SIZE, Color::WHITE),
+        ).expect("Can't render text");
+        let area = image.area().translate(top_left);
+        Self {
+            area,
+            z_index,
+            image,
+        }
+    }
+}
+
+impl Widget for Label {
+    fn bounding_box(&self) -> Rectangle {
+        self.area
+    }
+
+    fn maybe_activate(&self) -> Option<GameEvent> {
+        None
+    }
+
+    fn draw(&self, window: &mut Window) -> Result<()> {
+        window.draw_ex(&self.area, Img(&self.image), Transform::IDENTITY, self.z_index);
+        Ok(())
+    }
+
+    fn update_hovered(&mut self, _pointer_position: Vector) {}
+}
+
+/// Glowing frame around a card, drawn beneath it. Not interactive.
+#[derive(Debug)]
+pub struct Highlight {
+    area: Rectangle,
+    z_index: f32,
+}
+
+impl Highlight {
+    pub fn new(card_area: Rectangle, z_index: f32) -> Self {
+        let frame = Vector::new(PAD_SIZE / 2.0, PAD_SIZE / 2.0);
+        Self {
+            area: Rectangle::new(card_area.pos - frame, card_area.size + frame * 2.0),
+            z_index,
+        }
+    }
+}
+
+impl Widget for Highlight {
+    fn bounding_box(&self) -> Rectangle {
+        self.area
+    }
+
+    fn maybe_activate(&self) -> Option<GameEvent> {
+        None
+    }
+
+    fn draw(&self, window: &mut Window) -> Result<()> {
+        window.draw_ex(&self.area, Col(Color::from_rgba(230, 200, 40, 1.0)), Transform::IDENTITY, self.z_index);
+        Ok(())
+    }
+
+    fn update_hovered(&mut self, _pointer_position: Vector) {}
+}